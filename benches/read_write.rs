@@ -7,11 +7,11 @@ mod bench {
 
     use std::fs::{OpenOptions, remove_file};
 
-    use adafruit_gps::GpsSentence;
+    use adafruit_gps::{GpsSentence, NmeaTime, Talker};
     use adafruit_gps::gga::{GgaData, SatFix};
 
-    const SENTENCE: GpsSentence = GpsSentence::GGA(GgaData {
-        utc: 100.0,
+    const SENTENCE: GpsSentence = GpsSentence::GGA(Talker::Gps, GgaData {
+        utc: NmeaTime { hour: 0, minute: 1, second: 0, millis: 0 },
         lat: Some(51.55465),
         long: Some(-0.05632),
         sat_fix: SatFix::DgpsFix,
@@ -33,7 +33,7 @@ mod bench {
 
         b.iter(|| {
             for s in v.iter() {
-                s.clone().append_to("bench_test1")
+                s.clone().append_to("bench_test1").unwrap();
             }
             let _ = remove_file("bench_test1");
         });
@@ -43,7 +43,7 @@ mod bench {
     fn bench_append(b: &mut Bencher) {
         b.iter(|| {
             for _ in 0..VECTOR_SIZE {
-                SENTENCE.append_to("bench_test2")
+                SENTENCE.append_to("bench_test2").unwrap();
             }
             let _ = remove_file("bench_test2");
         });
@@ -53,7 +53,7 @@ mod bench {
     fn bench_append_single_struct(b: &mut Bencher) {
         b.iter(|| {
             for _ in 0..1 {
-                SENTENCE.append_to("bench_test2")
+                SENTENCE.append_to("bench_test2").unwrap();
             }
             let _ = remove_file("bench_test2");
         });
@@ -70,7 +70,7 @@ mod bench {
     #[bench]
     fn bench_read(b: &mut Bencher) {
         for _ in 0..VECTOR_SIZE {
-            SENTENCE.append_to("bench_read")
+            SENTENCE.append_to("bench_read").unwrap();
         }
 
         b.iter(|| {
@@ -2,17 +2,17 @@ use adafruit_gps::{Gps, GpsSentence};
 use adafruit_gps::NmeaOutput;
 
 fn main() {
-    let mut gps = Gps::new("/dev/serial0", "9600");
+    let mut gps = Gps::new("/dev/serial0", "9600").expect("Could not open the gps port");
     gps.pmtk_220_set_nmea_updaterate("1000");
-    gps.pmtk_314_api_set_nmea_output(NmeaOutput{ gll: 1, rmc: 0, vtg: 0, gga: 0, gsa: 1, gsv: 0, pmtkchn_interval: 0 });
+    gps.pmtk_314_api_set_nmea_output(NmeaOutput{ gll: 1, rmc: 0, vtg: 0, gga: 0, gsa: 1, gsv: 0, zda: 0, pmtkchn_interval: 0 });
 
     for _ in 0..100 {
         let values = gps.update();
-        values.append_to("main_test");
+        values.append_to("main_test").unwrap();
     }
 
     // Read a file of structs. Always gives it as a vector.
-    let gps: Vec<GpsSentence> = GpsSentence::read_from("main_test");
+    let gps: Vec<GpsSentence> = GpsSentence::read_from("main_test").unwrap();
     println!("{:?}", gps);
 
     // If you have a Vec<GpsSentence> and you wish to save it, do the following:
@@ -20,7 +20,7 @@ fn main() {
     // a trait on Vec<GpsSentence>.
     let v: Vec<GpsSentence> = Vec::new();
     for s in v.iter() {
-        s.clone().append_to("bench_test1")
+        s.clone().append_to("bench_test1").unwrap();
     }
 
 }
\ No newline at end of file
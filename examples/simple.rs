@@ -12,7 +12,7 @@ fn main() {
     let port = args.get(2).unwrap();
 
     // Open the port that is connected to the GPS module.
-    let mut gps = Gps::new(port, baud_rate);
+    let mut gps = Gps::new(port, baud_rate).expect("Could not open the gps port");
 
     // gps.init() requires the update rate for the gps (1000 miliseconds (1Hz) is default)
     // It returns a hash map to tell you if setting the update rate was successful and if the
@@ -23,7 +23,7 @@ fn main() {
     // If setting the update_rate consistently fails for faster updates, see exmaples/increase_frequency.rs
 
     // Give settings here.
-    gps.pmtk_314_api_set_nmea_output(NmeaOutput{gga: 1, gsa: 1, gsv: 1,  gll: 1, rmc: 1, vtg: 1, pmtkchn_interval: 1 });
+    gps.pmtk_314_api_set_nmea_output(NmeaOutput{gga: 1, gsa: 1, gsv: 1,  gll: 1, rmc: 1, vtg: 1, zda: 1, pmtkchn_interval: 1 });
     let r = gps.pmtk_220_set_nmea_updaterate("1000");
     println!("{:?}", r);
 
@@ -38,12 +38,12 @@ fn main() {
             GpsSentence::InvalidSentence => println!("Invalid sentence, try again"),
             GpsSentence::InvalidBytes => println!("Invalid bytes given, try again"),
             GpsSentence::NoConnection => println!("No connection with gps"),
-            GpsSentence::GGA(sentence) => {
+            GpsSentence::GGA(_, sentence) => {
                 println!("UTC: {}\nLat:{}, Long:{}, Sats:{}, MSL Alt:{}",
                          sentence.utc, sentence.lat.unwrap_or(0.0), sentence.long.unwrap_or(0.0), sentence.satellites_used,
                 sentence.msl_alt.unwrap_or(0.0));
             }
-            GpsSentence::GSA(sentence) => {
+            GpsSentence::GSA(_, sentence) => {
                 println!("PDOP:{}, VDOP:{}, HDOP:{}",
                          sentence.pdop.unwrap_or(0.0), sentence.vdop.unwrap_or(0.0), sentence.hdop.unwrap_or(0.0))
             }
@@ -24,9 +24,9 @@ pub fn inverse_vincenty(start: &Coordinate, end: &Coordinate) -> f64 {
     const b: f64 = (1_f64 - f) * a;  // radius at the poles - 6356752.314245 meters in WGS-84
 
 
-    let U1: f64 = ((1_f64 - f) * start.latitude.unwrap().to_radians().tan() as f64).atan();  // Reduced latitude (latitude on the auxiliary sphere)
-    let U2: f64 = ((1_f64 - f) * end.latitude.unwrap().to_radians().tan() as f64).atan();
-    let L: f64 = (end.longitude.unwrap().to_radians() - start.longitude.unwrap().to_radians()) as f64;
+    let U1: f64 = ((1_f64 - f) * start.latitude.unwrap().to_radians().tan()).atan();  // Reduced latitude (latitude on the auxiliary sphere)
+    let U2: f64 = ((1_f64 - f) * end.latitude.unwrap().to_radians().tan()).atan();
+    let L: f64 = end.longitude.unwrap().to_radians() - start.longitude.unwrap().to_radians();
 
     let sinU1 = U1.sin();
     let cosU1 = U1.cos();
@@ -89,10 +89,10 @@ pub fn inverse_vincenty(start: &Coordinate, end: &Coordinate) -> f64 {
 ///
 /// (Haversine wiki) [https://en.wikipedia.org/wiki/Haversine_formula]
 pub fn haversine(start: &Coordinate, end: &Coordinate) -> f64 {
-    let lat1 = start.latitude.unwrap().to_radians() as f64;
-    let lat2 = end.latitude.unwrap().to_radians() as f64;
-    let long1 = start.longitude.unwrap().to_radians() as f64;
-    let long2 = end.longitude.unwrap().to_radians() as f64;
+    let lat1 = start.latitude.unwrap().to_radians();
+    let lat2 = end.latitude.unwrap().to_radians();
+    let long1 = start.longitude.unwrap().to_radians();
+    let long2 = end.longitude.unwrap().to_radians();
     let mean_earth_radius = 6371008.8; // https://en.wikipedia.org/wiki/Earth_radius#Global_average_radii
     let havlat = ((lat2 - lat1) / 2_f64).sin().powi(2);
     let havlong = ((long2 - long1) / 2_f64).sin().powi(2);
@@ -156,13 +156,13 @@ impl Kinematics for Vec<(f64, f64)> {
 mod test_distances {
     use super::{haversine, inverse_vincenty, Coordinate, DeltaCoordinates, Kinematics};
 
-    const SMALL1: Coordinate = Coordinate { utc: (0.0), latitude: Some(51.55814), longitude: Some(0.02955), altitude: Some(0.0) };
-    const SMALL2: Coordinate = Coordinate { utc: (0.0), latitude: Some(51.55795), longitude: Some(0.03014), altitude: Some(100.0) };
-    const SMALL3: Coordinate = Coordinate { utc: (0.0), latitude: Some(51.55795), longitude: Some(0.03014), altitude: Some(0.0) };
+    const SMALL1: Coordinate = Coordinate { utc: (0.0), latitude: Some(51.55814), longitude: Some(0.02955), altitude: Some(0.0), accuracy: None };
+    const SMALL2: Coordinate = Coordinate { utc: (0.0), latitude: Some(51.55795), longitude: Some(0.03014), altitude: Some(100.0), accuracy: None };
+    const SMALL3: Coordinate = Coordinate { utc: (0.0), latitude: Some(51.55795), longitude: Some(0.03014), altitude: Some(0.0), accuracy: None };
 
-    const LONDON: Coordinate = Coordinate { utc: (0.0), latitude: Some(51.500821), longitude: Some(-0.126670), altitude: Some(0.0) };
-    const PARIS: Coordinate = Coordinate { utc: (0.0), latitude: Some(48.858788), longitude: Some(2.293746), altitude: Some(0.0) };
-    const SYDNEY: Coordinate = Coordinate { utc: (0.0), latitude: Some(-33.852239), longitude: Some(151.210675), altitude: Some(0.0) };
+    const LONDON: Coordinate = Coordinate { utc: (0.0), latitude: Some(51.500821), longitude: Some(-0.126670), altitude: Some(0.0), accuracy: None };
+    const PARIS: Coordinate = Coordinate { utc: (0.0), latitude: Some(48.858788), longitude: Some(2.293746), altitude: Some(0.0), accuracy: None };
+    const SYDNEY: Coordinate = Coordinate { utc: (0.0), latitude: Some(-33.852239), longitude: Some(151.210675), altitude: Some(0.0), accuracy: None };
 
     #[test]
     fn vincenty_same_point() {
@@ -4,23 +4,60 @@
 /// Make a structure of a series of points and implement.
 /// At any point you are doing stuff to a series of points.
 
-//todo - size of error for a long lat: 51.0, 1.0 is x m^2 area.
-// todo - expected distance error for a given pdop.
+use adafruit_gps::gst::GstData;
 
 pub mod kinematics;
 pub mod position;
 
 /// This is the basic coordinate data for a single point in space.
 ///
-/// - UTC is used when calculating speed (relative UTC is needed)
+/// - UTC is used when calculating speed (relative UTC is needed). It's seconds since midnight.
 /// - altitude is used when measuring distance and actually calculates euclidian distance between
 /// points. If not required just put altitude to 0 and it will not affect calculations.
+/// - accuracy is the gps's own error estimate for the fix, if it sent a GST for it.
 #[derive(Default, PartialEq, Debug)]
 pub struct Coordinate {
     pub utc: f64,
-    pub latitude: Option<f32>,
-    pub longitude: Option<f32>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
     pub altitude: Option<f32>,
+    pub accuracy: Option<Accuracy>,
+}
+
+/// Standard deviations of the error of a single fix, in metres, from a GST sentence.
+///
+/// - latitude and longitude are always given, altitude and the error ellipse only if the gps
+///   sent them.
+#[derive(Default, PartialEq, Debug, Clone, Copy)]
+pub struct Accuracy {
+    pub latitude: f32,
+    pub longitude: f32,
+    pub altitude: Option<f32>,
+    pub semi_major: Option<f32>,
+    pub semi_minor: Option<f32>,
+}
+
+impl Accuracy {
+    /// None if the GST has no latitude or longitude standard deviation.
+    pub fn from_gst(gst: &GstData) -> Option<Accuracy> {
+        Some(Accuracy {
+            latitude: gst.lat_std_dev?,
+            longitude: gst.long_std_dev?,
+            altitude: gst.alt_std_dev,
+            semi_major: gst.semi_major,
+            semi_minor: gst.semi_minor,
+        })
+    }
+
+    /// Horizontal standard deviation in metres, the distance error expected for the fix.
+    pub fn horizontal(&self) -> f32 {
+        (self.latitude * self.latitude + self.longitude * self.longitude).sqrt()
+    }
+
+    /// Area of the one standard deviation error ellipse in m^2, if the gps gave the ellipse.
+    pub fn ellipse_area(&self) -> Option<f32> {
+        Some(core::f32::consts::PI * self.semi_major? * self.semi_minor?)
+    }
 }
 
 
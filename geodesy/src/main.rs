@@ -10,13 +10,13 @@ fn main() {
     // uploaded to google earth for visualisation.
     let flight_num = "3";
     let mut mod_vec = Vec::new();
-    let vec = GpsSentence::read_from(format!("./feldspar5-{}_gps", flight_num).as_str());
+    let vec = GpsSentence::read_from(format!("./feldspar5-{}_gps", flight_num).as_str()).unwrap();
     for item in vec.iter() {
         match item {
-            GpsSentence::GGA(s) => {
+            GpsSentence::GGA(talker, s) => {
                 let mut new_item = s.clone();
                 new_item.geoidal_sep = Some(47.0);
-                mod_vec.push(GpsSentence::GGA(new_item))
+                mod_vec.push(GpsSentence::GGA(*talker, new_item))
             }
             _ => {}
         }
@@ -7,7 +7,7 @@ use crate::kinematics::{inverse_vincenty};
 /// # Position Accuracy
 /// Given a set of coordinates, produce the average longitude and latitude,
 
-use super::Coordinate;
+use super::{Accuracy, Coordinate};
 use std::fs::File;
 use std::io::Write;
 
@@ -23,40 +23,60 @@ impl GpsSentenceConverter for Vec<GpsSentence> {
     /// include_geoidal_separation argument allows for altitude to equal mean sea level alt + geoidal
     /// separation for height above the ground. If there is no geoidal separation data available
     /// then nothing is added.
+    ///
+    /// A GST sentence gives its accuracy to the coordinates with the same UTC, whether they come
+    /// before or after it.
     fn to_coords(&self, include_geoidal_separation: bool) -> Vec<Coordinate> {
-        let mut vec_coord = Vec::new();
+        let mut vec_coord: Vec<Coordinate> = Vec::new();
+        let mut last_gst: Option<(f64, Accuracy)> = None;
         for s in self.iter() {
             match s {
-                GpsSentence::GGA(sentence) => {
+                GpsSentence::GGA(_, sentence) => {
                     let mut gga = Coordinate {
-                        utc: sentence.utc,
+                        utc: sentence.utc.seconds_of_day(),
                         latitude: sentence.lat,
                         longitude: sentence.long,
                         altitude: sentence.msl_alt,
+                        accuracy: None,
                     };
                     if include_geoidal_separation && gga.altitude.is_some() && sentence.geoidal_sep.is_some(){
                         gga.altitude = Some(gga.altitude.unwrap() + sentence.geoidal_sep.unwrap());
                     }
                     vec_coord.push(gga);
                 }
-                GpsSentence::GLL(sentence) => {
+                GpsSentence::GLL(_, sentence) => {
                     vec_coord.push(Coordinate {
-                        utc: sentence.utc.unwrap_or(0.0),
+                        utc: sentence.utc.map_or(0.0, |utc| utc.seconds_of_day()),
                         latitude: sentence.latitude,
                         longitude: sentence.longitude,
                         altitude: None,
+                        accuracy: None,
                     });
                 }
-                GpsSentence::RMC(sentence) => {
+                GpsSentence::RMC(_, sentence) => {
                     vec_coord.push(Coordinate {
-                        utc: sentence.utc,
+                        utc: sentence.utc.map_or(0.0, |utc| utc.seconds_of_day()),
                         latitude: sentence.latitude,
                         longitude: sentence.longitude,
                         altitude: None,
+                        accuracy: None,
                     });
                 }
+                GpsSentence::GST(_, sentence) => {
+                    last_gst = Accuracy::from_gst(sentence).map(|accuracy| (sentence.utc.seconds_of_day(), accuracy));
+                    if let Some((utc, accuracy)) = last_gst {
+                        for coord in vec_coord.iter_mut().rev().take_while(|c| c.utc == utc) {
+                            coord.accuracy = Some(accuracy);
+                        }
+                    }
+                }
                 _ => {}
             };
+            if let (Some((utc, accuracy)), Some(coord)) = (last_gst, vec_coord.last_mut()) {
+                if coord.utc == utc {
+                    coord.accuracy = Some(accuracy);
+                }
+            }
         }
         vec_coord
     }
@@ -76,22 +96,41 @@ impl Position for Vec<Coordinate> {
     ///
     /// Returns a Location structure with each long, lat and altitude being the average. UTC is 0.
     ///
+    /// If every point has an accuracy (from GST), each is weighted by the inverse of its
+    /// variance, so the better fixes count for more. Otherwise all points count the same, as they
+    /// do if any accuracy is 0 (or less), which would be an infinite weight.
+    ///
     /// Assume that all points in the Vec<Location> are valid.
     fn average_long_lat(&self) -> Coordinate {
         let mut x: f64 = 0.0;
         let mut y: f64 = 0.0;
         let mut z: f64 = 0.0;
         let mut altitude: f64 = 0.0;
+        let mut total_weight: f64 = 0.0;
+        let mut total_alt_weight: f64 = 0.0;
+
+        let weighted = self.iter()
+            .all(|location| matches!(location.accuracy, Some(a) if a.horizontal() > 0.0));
+        let alt_weighted = self.iter()
+            .all(|location| matches!(location.accuracy.and_then(|a| a.altitude), Some(a) if a > 0.0));
 
         for location in self.iter() {
-            x += (location.latitude.unwrap().cos() * location.longitude.unwrap().cos()) as f64;
-            y += (location.latitude.unwrap().cos() * location.longitude.unwrap().sin()) as f64;
-            z += location.latitude.unwrap().sin() as f64;
-            altitude += location.altitude.unwrap() as f64;
+            let weight = if weighted {
+                1.0 / (location.accuracy.unwrap().horizontal() as f64).powi(2)
+            } else { 1.0 };
+            let alt_weight = if alt_weighted {
+                1.0 / (location.accuracy.unwrap().altitude.unwrap() as f64).powi(2)
+            } else { 1.0 };
+            x += weight * location.latitude.unwrap().cos() * location.longitude.unwrap().cos();
+            y += weight * location.latitude.unwrap().cos() * location.longitude.unwrap().sin();
+            z += weight * location.latitude.unwrap().sin();
+            altitude += alt_weight * location.altitude.unwrap() as f64;
+            total_weight += weight;
+            total_alt_weight += alt_weight;
         }
-        x = x / self.len() as f64;
-        y = y / self.len() as f64;
-        z = z / self.len() as f64;
+        x /= total_weight;
+        y /= total_weight;
+        z /= total_weight;
 
         let central_long = y.atan2(x);
         let central_sq_rt = (x * x + y * y).sqrt();
@@ -99,21 +138,23 @@ impl Position for Vec<Coordinate> {
 
         let average_long = central_long.to_degrees();
         let average_lat = central_lat.to_degrees();
-        let average_alt: f64 = altitude / self.len() as f64;
+        let average_alt: f64 = altitude / total_alt_weight;
 
         return Coordinate {
-            latitude: Some(average_lat as f32),
-            longitude: Some(average_long as f32),
+            latitude: Some(average_lat),
+            longitude: Some(average_long),
             altitude: Some(average_alt as f32),
             utc: 0.0,
+            accuracy: None,
         };
     }
 
 
-    /// Plots the longitude and latitiude coordinates on a 2d graph using plotters.
+    /// Plots the longitude and latitiude coordinates on a 2d graph using plotters. f32 is plenty
+    /// for a 600 pixel plot.
     fn plot_positions(&self, name: &str) {
         let mut positions: Vec<(f32, f32)> = self.into_iter()
-            .map(|x| (x.longitude.unwrap(), x.latitude.unwrap())).collect();
+            .map(|x| (x.longitude.unwrap() as f32, x.latitude.unwrap() as f32)).collect();
         positions.retain(|x| *x != (0.0, 0.0));  // Remove all (0,0) coords.
 
         let latitudes: Vec<f32> = positions.clone().into_iter().map(|x| x.0).collect();
@@ -200,3 +241,52 @@ impl Position for Vec<Coordinate> {
     }
 }
 
+
+#[cfg(test)]
+mod test_accuracy {
+    use adafruit_gps::GpsSentence;
+    use super::{GpsSentenceConverter, Position};
+    use crate::Coordinate;
+
+    #[test]
+    fn gst_accuracy_to_coords() {
+        let sentences: Vec<GpsSentence> = vec![
+            GpsSentence::from_sentence("$GPGGA,131613.000,5132.7314,N,00005.9099,W,1,9,1.17,42.4,M,47.0,M,,*7E"),
+            GpsSentence::from_sentence("$GPGST,131613.000,0.6,2.3,2.0,273.6,2.3,2.0,3.1*64"),
+            GpsSentence::from_sentence("$GPGST,131614.000,0.6,2.3,2.0,273.6,1.2,1.6,3.1*64"),
+            GpsSentence::from_sentence("$GPRMC,131614.000,A,5132.7315,N,00005.9098,W,0.17,0.00,110520,,,A*75"),
+            GpsSentence::from_sentence("$GPRMC,131615.000,A,5132.7316,N,00005.9097,W,0.17,0.00,110520,,,A*78"),
+        ];
+        let coords = sentences.to_coords(false);
+        assert_eq!(coords.len(), 3);
+        assert_eq!(coords[0].accuracy.unwrap().latitude, 2.3);
+        assert!((coords[1].accuracy.unwrap().horizontal() - 2.0).abs() < 1e-6);
+        assert_eq!(coords[2].accuracy, None);
+        assert_eq!(coords[0].latitude, Some(51.0 + 32.7314 / 60.0));
+    }
+
+    #[test]
+    fn weighted_average() {
+        let accuracy = |m: f32| Some(crate::Accuracy { latitude: m, longitude: 0.0, altitude: Some(m), ..Default::default() });
+        let coords = vec![
+            Coordinate { utc: 0.0, latitude: Some(0.0), longitude: Some(0.0), altitude: Some(0.0), accuracy: accuracy(1.0) },
+            Coordinate { utc: 1.0, latitude: Some(0.0), longitude: Some(0.0), altitude: Some(30.0), accuracy: accuracy(2.0) },
+        ];
+        // Weights are 1 and 1/4.
+        assert_eq!(coords.average_long_lat().altitude, Some(6.0));
+    }
+
+    #[test]
+    fn zero_accuracy_average() {
+        let accuracy = |m: f32| Some(crate::Accuracy { latitude: m, longitude: 0.0, altitude: Some(m), ..Default::default() });
+        let coords = vec![
+            Coordinate { utc: 0.0, latitude: Some(0.0), longitude: Some(0.0), altitude: Some(0.0), accuracy: accuracy(0.0) },
+            Coordinate { utc: 1.0, latitude: Some(0.0), longitude: Some(0.0), altitude: Some(30.0), accuracy: accuracy(2.0) },
+        ];
+        // A 0.000 accuracy falls back to equal weights instead of an infinite one.
+        let average = coords.average_long_lat();
+        assert_eq!(average.altitude, Some(15.0));
+        assert_eq!(average.latitude, Some(0.0));
+        assert_eq!(average.longitude, Some(0.0));
+    }
+}
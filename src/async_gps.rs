@@ -0,0 +1,551 @@
+//! # Async gps
+//! AsyncGps is Gps for async code: update, send_command and the PMTK queries are async fns, so
+//! reading the gps doesn't need a thread of its own. The sentences are parsed by the same code as
+//! the blocking Gps.
+//!
+//! Any embedded-io-async port works, eg an embassy uart on a microcontroller. With the tokio feature,
+//! TokioPort makes a tokio serial port, pipe or TCP stream into one.
+//! ```ignore
+//! let mut gps = AsyncGps::from_port(uart);
+//! gps.pmtk_220_set_nmea_updaterate("1000").await;
+//! let sentence = gps.update().await;
+//! ```
+//!
+//! ## Options and timeouts
+//! AsyncGps reads with the same [GpsOptions](../gps/struct.GpsOptions.html) as Gps, given with
+//! AsyncGps::from_port_with_options. Lines are cut off at max_sentence_length, and with std a line
+//! that takes longer than no_data_timeout gives NoConnection. That is checked as bytes come in, so
+//! a read that never returns still waits for as long as the port does. Without std there is no
+//! clock at all. Wrap update in your executor's timeout (eg embassy_time::with_timeout) if the gps
+//! could go quiet. TokioPort times a read out itself after its read_timeout, and update then gives
+//! NoConnection.
+
+#[cfg(feature = "std")]
+use std::time::SystemTime;
+
+use embedded_io_async::{Read, Write};
+use heapless::{String, Vec};
+
+use crate::nmea::gsv::GsvAssembler;
+use crate::open_gps::gps::{
+    finish_line, GpsOptions, GpsSentence, MAX_SENTENCE_LENGTH, parse_line, PortConnection, push_byte, SentenceParsers,
+};
+use crate::pmtk::send_pmtk::*;
+
+/// Gps over an async port.
+pub struct AsyncGps<T> {
+    pub port: T,
+    pub options: GpsOptions,
+    pub gsv: GsvAssembler,
+    pub parsers: SentenceParsers,
+}
+
+impl<T: Read + Write> AsyncGps<T> {
+    /// Gives an AsyncGps reading from and writing to any async byte transport.
+    pub fn from_port(port: T) -> AsyncGps<T> {
+        AsyncGps::from_port_with_options(port, GpsOptions::default())
+    }
+
+    /// Reads with the no data time out and sentence length of the options. The port time out
+    /// is up to whatever opened the port.
+    pub fn from_port_with_options(port: T, options: GpsOptions) -> AsyncGps<T> {
+        AsyncGps { port, options, gsv: GsvAssembler::new(), parsers: SentenceParsers::new() }
+    }
+
+    /// Reads a full sentence from the port.
+    ///
+    /// NoConnection if the port gives an error (eg a timeout), reaches the end of the stream or,
+    /// with std, the line takes longer than the no data time out.
+    pub async fn read_line(&mut self) -> PortConnection {
+        let mut buffer: [u8; 1] = [0; 1];
+        let mut output: Vec<u8, MAX_SENTENCE_LENGTH> = Vec::new();
+        let options = self.options;
+        #[cfg(feature = "std")]
+        let start = SystemTime::now();
+        loop {
+            match self.port.read(&mut buffer).await {
+                Ok(0) => return PortConnection::NoConnection,
+                Ok(_buffer_size) => {
+                    if push_byte(&mut output, buffer[0], options.max_sentence_length) {
+                        break;
+                    }
+                }
+                Err(_e) => return PortConnection::NoConnection,
+            }
+            #[cfg(feature = "std")]
+            {
+                if start.elapsed().unwrap_or_default() > options.no_data_timeout {
+                    return PortConnection::NoConnection;
+                }
+            }
+        }
+        finish_line(output)
+    }
+
+    /// Reads the next sentence from the gps, see Gps::update.
+    pub async fn update(&mut self) -> GpsSentence {
+        loop {
+            let line = self.read_line().await;
+            if let Some(sentence) = parse_line(line, Some(&mut self.gsv), &self.parsers) {
+                return sentence;
+            }
+        }
+    }
+
+    /// Send the PMTK command.
+    pub async fn send_command(&mut self, cmd: &str) {
+        //! Input: no $ and no *checksum.
+        let cmd = add_checksum(cmd);
+        if self.port.write_all(cmd.as_bytes()).await.is_ok() {
+            let _ = self.port.flush().await;
+        }
+    }
+
+    /// Check for a PMTK001 return.
+    pub async fn pmtk_001(&mut self, search_depth: i32) -> Pmtk001Ack {
+        for _i in 0..search_depth {
+            match self.read_line().await {
+                PortConnection::Valid(line) => {
+                    if let Some(ack) = pmtk_001_ack(line.as_str()) {
+                        return ack;
+                    }
+                }
+                PortConnection::NoConnection | PortConnection::InvalidBytes(_) => return Pmtk001Ack::NoPacket,
+            }
+        }
+        Pmtk001Ack::NoPacket
+    }
+
+    /// Check for PMTK500 style return.
+    pub async fn pmtk_500(&mut self) -> Option<String<MAX_SENTENCE_LENGTH>> {
+        //! Return the string without checksum.
+        for _i in 0..10 {
+            match self.read_line().await {
+                PortConnection::Valid(line) => {
+                    if let Some(reply) = pmtk_500_reply(line.as_str()) {
+                        return Some(reply);
+                    }
+                }
+                PortConnection::NoConnection | PortConnection::InvalidBytes(_) => return None,
+            }
+        }
+        None
+    }
+
+    /// Checks if the GPS rebooted.
+    pub async fn pmtk_startup(&mut self) -> bool {
+        for _i in 0..10 {
+            match self.read_line().await {
+                PortConnection::Valid(line) => {
+                    if is_pmtk_startup(line.as_str()) {
+                        return true;
+                    }
+                }
+                PortConnection::NoConnection | PortConnection::InvalidBytes(_) => return false,
+            }
+        }
+        false
+    }
+
+    /// Restart with all data intact.
+    pub async fn pmtk_101_cmd_hot_start(&mut self) -> bool {
+        self.send_command("PMTK101").await;
+        self.pmtk_startup().await
+    }
+
+    /// Hot Restart without using Ephemeris data.
+    pub async fn pmtk_102_cmd_warm_start(&mut self) -> bool {
+        self.send_command("PMTK102").await;
+        self.pmtk_startup().await
+    }
+
+    /// Restart with current settings, but no navigation data.
+    pub async fn pmtk_103_cmd_cold_start(&mut self) -> bool {
+        self.send_command("PMTK103").await;
+        self.pmtk_startup().await
+    }
+
+    /// Full cold start resets all setting to default.
+    pub async fn pmtk_104_cmd_full_cold_start(&mut self) -> bool {
+        self.send_command("PMTK104").await;
+        self.pmtk_startup().await
+    }
+
+    /// Set the update rate, as miliseconds from 100 (100Hz) to 10_000 (0.1Hz). 1000 is default.
+    pub async fn pmtk_220_set_nmea_updaterate(&mut self, update_rate: &str) -> Pmtk001Ack {
+        self.send_command(&update_rate_command(update_rate)).await;
+        self.pmtk_001(10).await
+    }
+
+    /// Set Differental Gps mode, see Gps::pmtk_301_api_set_dgps_mode.
+    pub async fn pmtk_301_api_set_dgps_mode(&mut self, dgps_mode: DgpsMode) -> Pmtk001Ack {
+        if let Some(cmd) = dgps_mode_command(&dgps_mode) {
+            self.send_command(&cmd).await;
+        }
+        self.pmtk_001(10).await
+    }
+
+    /// Check what the current Differential Gps mode is.
+    pub async fn pmtk_401_api_q_dgps_mode(&mut self) -> DgpsMode {
+        self.send_command("PMTK401").await;
+        dgps_mode_reply(self.pmtk_500().await.as_deref())
+    }
+
+    /// Set SBAS (Satellite-based augmentation systems) enabled or disabled.
+    pub async fn pmtk_313_api_set_sbas_enabled(&mut self, sbas: Sbas) -> Pmtk001Ack {
+        if let Some(cmd) = sbas_command(&sbas) {
+            self.send_command(&cmd).await;
+        }
+        self.pmtk_001(10).await
+    }
+
+    /// Check if SBAS is enabled
+    pub async fn pmtk_413_api_q_sbas_enabled(&mut self) -> Sbas {
+        self.send_command("PMTK413").await;
+        sbas_reply(self.pmtk_500().await.as_deref())
+    }
+
+    /// Set what NMEA sentences are to be outputted as frequency, see Gps::pmtk_314_api_set_nmea_output.
+    pub async fn pmtk_314_api_set_nmea_output(&mut self, output: NmeaOutput) -> Pmtk001Ack {
+        self.send_command(&nmea_output_command(&output)).await;
+        self.pmtk_001(10).await
+    }
+
+    /// Gets current NMEA output frequency.
+    pub async fn pmtk_414_api_q_nmea_output(&mut self) -> NmeaOutput {
+        self.send_command("PMTK414").await;
+        nmea_output_reply(self.pmtk_500().await.as_deref())
+    }
+
+    /// Set SBAS mode, the gps restarts.
+    pub async fn pmtk_319_api_set_sbas_mode(&mut self, sbas_mode: SbasMode) -> bool {
+        if let Some(cmd) = sbas_mode_command(&sbas_mode) {
+            self.send_command(&cmd).await;
+        }
+        self.pmtk_startup().await
+    }
+
+    /// Check SBAS mode
+    pub async fn pmtk_419_api_q_sbas_mode(&mut self) -> SbasMode {
+        self.send_command("PMTK419").await;
+        sbas_mode_reply(self.pmtk_500().await.as_deref())
+    }
+
+    /// Gives GPS firmware release info. Blank string if no info found.
+    pub async fn pmtk_605_q_release(&mut self) -> String<MAX_SENTENCE_LENGTH> {
+        self.send_command("PMTK605").await;
+        release_reply(self.pmtk_500().await.as_deref())
+    }
+
+    /// Get EPO data: Extended Prediction Orbit tries to predict where satellites will be in the future.
+    pub async fn pmtk_607_q_epo_info(&mut self) -> EpoData {
+        self.send_command("PMTK607").await;
+        epo_reply(self.pmtk_500().await.as_deref())
+    }
+
+    /// Clear EPO data.
+    pub async fn pmtk_127_cmd_clear_epo(&mut self) -> Pmtk001Ack {
+        self.send_command("PMTK127").await;
+        self.pmtk_001(50).await
+    }
+
+    /// Set the navigation speed threshold, for MT3318 and MT3329 chips.
+    pub async fn pmtk_397_set_nav_speed_threshold(&mut self, nav_threshold: f32) -> Pmtk001Ack {
+        self.send_command(&nav_threshold_command(nav_threshold)).await;
+        self.pmtk_001(10).await
+    }
+
+    /// Set the navigation speed threshold, for MT3339 chips.
+    pub async fn pmtk_386_set_nav_speed_threshold(&mut self, nav_threshold: f32) -> Pmtk001Ack {
+        self.send_command(&nav_threshold_command(nav_threshold)).await;
+        self.pmtk_001(10).await
+    }
+
+    /// Gets current nav speed threshold.
+    pub async fn pmtk_447_q_nav_threshold(&mut self) -> f32 {
+        self.send_command("PMTK447").await;
+        nav_threshold_reply(self.pmtk_500().await.as_deref())
+    }
+
+    /// Puts gps on standby mode for power saving. Send any command to wake it up again.
+    pub async fn pmtk_161_cmd_standby_mode(&mut self) -> Pmtk001Ack {
+        self.send_command("PMTK161,0").await;
+        self.pmtk_001(10).await
+    }
+
+    /// Used with pmtk_225_cmd_periodic_mode to set periodic mode, see Gps::pmtk_223_set_al_dee_cfg.
+    pub async fn pmtk_223_set_al_dee_cfg(&mut self, sv: i8, snr: i8, ext_threshold: i32, ext_gap: i32) -> Pmtk001Ack {
+        self.send_command(&al_dee_cfg_command(sv, snr, ext_threshold, ext_gap)).await;
+        self.pmtk_001(10).await
+    }
+
+    /// Sets periodic mode settings, see Gps::pmtk_225_cmd_periodic_mode.
+    pub async fn pmtk_225_cmd_periodic_mode(
+        &mut self,
+        run_type: u8,
+        run_time: u32,
+        sleep_time: u32,
+        second_run_time: u32,
+        second_sleep_time: u32,
+    ) -> Pmtk001Ack {
+        self.send_command(&periodic_mode_command(run_type, run_time, sleep_time, second_run_time, second_sleep_time)).await;
+        self.pmtk_001(10).await
+    }
+
+    /// Active Interference Calcellation to counter jamming an enterfearance.
+    pub async fn pmtk_286_cmd_aic_mode(&mut self, aic: bool) -> Pmtk001Ack {
+        self.send_command(&aic_mode_command(aic)).await;
+        self.pmtk_001(10).await
+    }
+
+    /// Set EASY status. True: enable, False: disable.
+    pub async fn pmtk_869_cmd_easy_enable(&mut self, enable_easy: bool) -> Pmtk001Ack {
+        self.send_command(&easy_enable_command(enable_easy)).await;
+        self.pmtk_001(10).await
+    }
+
+    /// Get current EASY status
+    pub async fn pmtk_869_cmd_easy_query(&mut self) -> bool {
+        self.send_command("PMTK869,0").await;
+        easy_reply(self.pmtk_500().await.as_deref())
+    }
+
+    /// Configure LOCUS interval, seconds.
+    pub async fn pmtk_187_locus_config(&mut self, locus_interval: i8) -> Pmtk001Ack {
+        self.send_command(&locus_config_command(locus_interval)).await;
+        self.pmtk_001(10).await
+    }
+
+    /// Set DATUM, 0 = WGS84 (default).
+    pub async fn pmtk_330_api_set_datum(&mut self, datum: u16) -> Pmtk001Ack {
+        self.send_command(&datum_command(datum)).await;
+        self.pmtk_001(10).await
+    }
+
+    /// Query current DATUM
+    pub async fn pmtk_430_api_q_datum(&mut self) -> u16 {
+        self.send_command("PMTK430").await;
+        datum_reply(self.pmtk_500().await.as_deref())
+    }
+
+    /// Enable or disable the QZSS NMEA format. Default is disable.
+    pub async fn pmtk_351_api_set_support_qzss_nmea(&mut self, enable_qzss: bool) -> Pmtk001Ack {
+        self.send_command(&qzss_nmea_command(enable_qzss)).await;
+        self.pmtk_001(10).await
+    }
+
+    /// Enable or disable the QZSS function. Default is enable.
+    pub async fn pmtk_352_api_set_stop_qzss(&mut self, enable: bool) -> Pmtk001Ack {
+        self.send_command(&stop_qzss_command(enable)).await;
+        self.pmtk_001(10).await
+    }
+}
+
+#[cfg(feature = "tokio")]
+pub mod tokio_port {
+    //! Tokio transports as an embedded-io-async port.
+    use std::io;
+    use std::time::Duration;
+
+    use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufStream};
+
+    use super::AsyncGps;
+
+    /// A tokio AsyncRead + AsyncWrite (serial port, pipe, TCP stream from ser2net...) as an async port.
+    ///
+    /// Reads are buffered. A read that gets nothing for read_timeout (default 1 second, like the
+    /// blocking Gps) fails with io::ErrorKind::TimedOut.
+    pub struct TokioPort<T: AsyncRead + AsyncWrite> {
+        pub stream: BufStream<T>,
+        pub read_timeout: Duration,
+    }
+
+    impl<T: AsyncRead + AsyncWrite + Unpin> TokioPort<T> {
+        pub fn new(stream: T) -> TokioPort<T> {
+            TokioPort { stream: BufStream::new(stream), read_timeout: Duration::from_secs(1) }
+        }
+    }
+
+    impl<T: AsyncRead + AsyncWrite> embedded_io_async::ErrorType for TokioPort<T> {
+        type Error = io::Error;
+    }
+
+    impl<T: AsyncRead + AsyncWrite + Unpin> embedded_io_async::Read for TokioPort<T> {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+            match tokio::time::timeout(self.read_timeout, self.stream.read(buf)).await {
+                Ok(result) => result,
+                Err(_elapsed) => Err(io::ErrorKind::TimedOut.into()),
+            }
+        }
+    }
+
+    impl<T: AsyncRead + AsyncWrite + Unpin> embedded_io_async::Write for TokioPort<T> {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, io::Error> {
+            self.stream.write(buf).await
+        }
+
+        async fn flush(&mut self) -> Result<(), io::Error> {
+            self.stream.flush().await
+        }
+    }
+
+    impl<T: AsyncRead + AsyncWrite + Unpin> AsyncGps<TokioPort<T>> {
+        /// Gives an AsyncGps reading from and writing to a tokio stream.
+        pub fn from_tokio(stream: T) -> AsyncGps<TokioPort<T>> {
+            AsyncGps::from_port(TokioPort::new(stream))
+        }
+    }
+
+    #[cfg(test)]
+    mod tokio_test {
+        use std::time::Duration;
+
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        use crate::GpsSentence;
+        use crate::pmtk::send_pmtk::Pmtk001Ack;
+
+        use super::AsyncGps;
+
+        #[test]
+        fn tokio_command_then_timeout() {
+            let runtime = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+            runtime.block_on(async {
+                let (gps_end, mut module_end) = tokio::io::duplex(1024);
+                let mut gps = AsyncGps::from_tokio(gps_end);
+                gps.port.read_timeout = Duration::from_millis(50);
+
+                module_end.write_all(b"$PMTK001,220,3*30\r\n").await.unwrap();
+                assert_eq!(gps.pmtk_220_set_nmea_updaterate("1000").await, Pmtk001Ack::Success);
+                let mut cmd = [0; 18];
+                module_end.read_exact(&mut cmd).await.unwrap();
+                assert_eq!(&cmd, b"$PMTK220,1000*1F\r\n");
+
+                // The module has gone quiet.
+                assert_eq!(gps.update().await, GpsSentence::NoConnection);
+            });
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod async_gps_test {
+    use core::convert::Infallible;
+    use core::future::Future;
+    use core::pin::pin;
+    use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+    use std::collections::VecDeque;
+
+    use crate::{GpsOptions, GpsSentence};
+    use crate::pmtk::send_pmtk::{DgpsMode, NmeaOutput, Pmtk001Ack};
+
+    use super::AsyncGps;
+
+    /// Runs a future that never waits on anything to the end.
+    fn block_on<F: Future>(future: F) -> F::Output {
+        fn clone(_data: *const ()) -> RawWaker {
+            RawWaker::new(core::ptr::null(), &VTABLE)
+        }
+        fn noop(_data: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+        let waker = unsafe { Waker::from_raw(RawWaker::new(core::ptr::null(), &VTABLE)) };
+        let mut context = Context::from_waker(&waker);
+        let mut future = pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+                return output;
+            }
+        }
+    }
+
+    /// Mock async port: gives the scripted bytes then the end of the stream. Records everything written.
+    struct MockPort {
+        input: VecDeque<u8>,
+        output: Vec<u8>,
+    }
+
+    impl MockPort {
+        fn new(input: &str) -> MockPort {
+            MockPort { input: input.bytes().collect(), output: Vec::new() }
+        }
+    }
+
+    impl embedded_io_async::ErrorType for MockPort {
+        type Error = Infallible;
+    }
+
+    impl embedded_io_async::Read for MockPort {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Infallible> {
+            let mut size = 0;
+            while size < buf.len() {
+                match self.input.pop_front() {
+                    Some(byte) => buf[size] = byte,
+                    None => break,
+                }
+                size += 1;
+            }
+            Ok(size)
+        }
+    }
+
+    impl embedded_io_async::Write for MockPort {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+            self.output.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+    }
+
+    #[test]
+    fn async_update() {
+        let mut gps = AsyncGps::from_port(MockPort::new(
+            "$GNGGA,131613.000,5132.7314,N,00005.9099,W,1,9,1.17,42.4,M,47.0,M,,*60\r\n\
+             $GPGSV,2,1,06,10,63,112,23,32,60,232,21,24,39,291,27,21,23,082,*77\r\n\
+             $GPGSV,2,2,06,25,21,320,28,20,12,052,*76\r\n",
+        ));
+        match block_on(gps.update()) {
+            GpsSentence::GGA(_, gga) => assert_eq!(gga.satellites_used, 9),
+            other => panic!("Expected GGA, got {:?}", other),
+        }
+        match block_on(gps.update()) {
+            GpsSentence::GSV(_, gsv) => assert_eq!(gsv.satellites.len(), 6),
+            other => panic!("Expected GSV, got {:?}", other),
+        }
+        assert_eq!(block_on(gps.update()), GpsSentence::NoConnection);
+    }
+
+    #[test]
+    fn async_command() {
+        let mut gps = AsyncGps::from_port(MockPort::new("$PMTK001,220,3*30\r\n"));
+        assert_eq!(block_on(gps.pmtk_220_set_nmea_updaterate("1000")), Pmtk001Ack::Success);
+        assert_eq!(gps.port.output, b"$PMTK220,1000*1F\r\n".to_vec());
+    }
+
+    #[test]
+    fn async_setting() {
+        let mut gps = AsyncGps::from_port(MockPort::new("$PMTK001,301,3*32\r\n$PMTK001,352,3*34\r\n"));
+        assert_eq!(block_on(gps.pmtk_301_api_set_dgps_mode(DgpsMode::WAAS)), Pmtk001Ack::Success);
+        assert_eq!(block_on(gps.pmtk_352_api_set_stop_qzss(true)), Pmtk001Ack::Success);
+        assert_eq!(gps.port.output, b"$PMTK301,2*2E\r\n$PMTK352,0*2A\r\n".to_vec());
+    }
+
+    #[test]
+    fn async_options() {
+        let options = GpsOptions::default().max_sentence_length(20);
+        let mut gps = AsyncGps::from_port_with_options(MockPort::new(
+            "$GPVTG,89.68,T,,M,0.00,N,0.0,K*5F\r\n",
+        ), options);
+        // Cut off at 20 bytes, so the checksum is lost.
+        assert_eq!(block_on(gps.update()), GpsSentence::InvalidSentence);
+    }
+
+    #[test]
+    fn async_query() {
+        let mut gps = AsyncGps::from_port(MockPort::new(
+            "$GPVTG,89.68,T,,M,0.00,N,0.0,K*5F\r\n$PMTK514,0,1,1,1,1,5,0,0,0,0,0,0,0,0,0,0,0,0,0*2B\r\n",
+        ));
+        let output = block_on(gps.pmtk_414_api_q_nmea_output());
+        assert_eq!(output, NmeaOutput { gll: 0, rmc: 1, vtg: 1, gga: 1, gsa: 1, gsv: 5, zda: 0, pmtkchn_interval: 0 });
+        assert_eq!(gps.port.output, b"$PMTK414*33\r\n".to_vec());
+    }
+}
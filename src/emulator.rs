@@ -0,0 +1,392 @@
+//! # Emulator
+//! A software PMTK gps module, for testing without the hardware.
+//!
+//! GpsEmulator is a GpsPort. Reading from it streams a fix's worth of NMEA sentences over and over,
+//! as fast as they are read. Writing PMTK commands to it changes its settings and queues the
+//! replies a real module gives: PMTK001 acks, PMTK5xx/7xx query replies and the PMTK011/PMTK010
+//! startup messages after a restart.
+//!
+//! - PMTK314 output frequencies decide which sentences are streamed for each fix.
+//! - PMTK220 fails when the sentences for one fix don't fit in a second's worth of bytes at the
+//!   baud rate, like the real module does.
+//! - PMTK251 changes the baud rate of the module. Until port_baud_rate is changed to match, reads
+//!   give garbage and commands are lost.
+//! ```ignore
+//! let mut gps = Gps::from_port(GpsEmulator::new());
+//! assert_eq!(gps.pmtk_220_set_nmea_updaterate("1000"), Pmtk001Ack::Success);
+//! assert_eq!(gps.pmtk_401_api_q_dgps_mode(), DgpsMode::WAAS);
+//! ```
+
+use std::collections::VecDeque;
+
+use crate::open_gps::gps::{GpsPort, is_valid_checksum, PortError};
+use crate::pmtk::send_pmtk::add_checksum;
+
+/// The fix streamed by GpsEmulator::new.
+pub const DEFAULT_SENTENCES: [&str; 8] = [
+    "$GNGGA,131613.000,5132.7314,N,00005.9099,W,1,9,1.17,42.4,M,47.0,M,,*60",
+    "$GPGSA,A,3,10,32,24,21,25,20,,,,,,,1.45,1.17,0.86*0B",
+    "$GPGSV,2,1,06,10,63,112,23,32,60,232,21,24,39,291,27,21,23,082,*77",
+    "$GPGSV,2,2,06,25,21,320,28,20,12,052,*76",
+    "$GNRMC,131613.000,A,5132.7314,N,00005.9099,W,0.00,89.68,110520,,,A*55",
+    "$GPVTG,89.68,T,,M,0.00,N,0.0,K*5F",
+    "$GNGLL,5132.7314,N,00005.9099,W,131613.000,A,A*5A",
+    "$GPZDA,131613.000,11,05,2020,,*54",
+];
+
+/// PMTK314 default: GLL off, RMC VTG GGA GSA every fix, GSV every 5th fix.
+const DEFAULT_NMEA_OUTPUT: [i8; 19] = [0, 1, 1, 1, 1, 5, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+
+/// Sentence types in the order of the PMTK314 fields, ZDA is field 17.
+const OUTPUT_SENTENCES: [&str; 6] = ["GLL", "RMC", "VTG", "GGA", "GSA", "GSV"];
+const ZDA_FIELD: usize = 17;
+
+/// Rough size of a sentence, for working out if an update rate fits in the baud rate.
+const SENTENCE_BYTES: u32 = 80;
+
+/// An emulated gps module.
+///
+/// All the settings are public so that a test can set up the module, or check what a command did.
+pub struct GpsEmulator {
+    /// One fix's worth of sentences, with $ and checksum but without \r\n.
+    pub sentences: Vec<String>,
+    /// Baud rate the module talks at.
+    pub baud_rate: u32,
+    /// Baud rate the port to the module was opened at.
+    pub port_baud_rate: u32,
+    /// Update rate in milliseconds.
+    pub update_rate: u32,
+    /// PMTK314 frequencies, see DEFAULT_NMEA_OUTPUT.
+    pub nmea_output: [i8; 19],
+    /// 0: No DGPS, 1: RTCM, 2: WAAS.
+    pub dgps_mode: u8,
+    pub sbas_enabled: bool,
+    /// 0: testing, 1: integrity.
+    pub sbas_mode: u8,
+    pub nav_threshold: f32,
+    pub easy_enabled: bool,
+    pub datum: u16,
+    pub release: String,
+    /// Every command received, without $ and checksum.
+    pub commands: Vec<String>,
+    output: VecDeque<u8>,
+    input: Vec<u8>,
+    fix: u32,
+}
+
+impl Default for GpsEmulator {
+    fn default() -> GpsEmulator {
+        GpsEmulator::new()
+    }
+}
+
+impl GpsEmulator {
+    /// A module at 9600 baud and 1Hz, streaming DEFAULT_SENTENCES.
+    pub fn new() -> GpsEmulator {
+        GpsEmulator::with_sentences(&DEFAULT_SENTENCES)
+    }
+
+    /// A module streaming the given sentences for every fix.
+    pub fn with_sentences(sentences: &[&str]) -> GpsEmulator {
+        let mut gps = GpsEmulator {
+            sentences: sentences.iter().map(|s| s.to_string()).collect(),
+            baud_rate: 9600,
+            port_baud_rate: 9600,
+            update_rate: 1000,
+            nmea_output: DEFAULT_NMEA_OUTPUT,
+            dgps_mode: 2,
+            sbas_enabled: true,
+            sbas_mode: 1,
+            nav_threshold: 0.0,
+            easy_enabled: true,
+            datum: 0,
+            release: String::new(),
+            commands: Vec::new(),
+            output: VecDeque::new(),
+            input: Vec::new(),
+            fix: 0,
+        };
+        gps.factory_settings();
+        gps
+    }
+
+    /// The settings a full cold start goes back to.
+    fn factory_settings(&mut self) {
+        self.update_rate = 1000;
+        self.nmea_output = DEFAULT_NMEA_OUTPUT;
+        self.dgps_mode = 2;
+        self.sbas_enabled = true;
+        self.sbas_mode = 1;
+        self.nav_threshold = 0.0;
+        self.easy_enabled = true;
+        self.datum = 0;
+        self.release = "AXN_5.1.7_3333_19020118,0027,PA1010D,1.0".to_string();
+    }
+
+    /// How often a sentence is output, 0 is never. Sentences PMTK314 doesn't cover are always output.
+    fn frequency(&self, sentence: &str) -> i8 {
+        match OUTPUT_SENTENCES.iter().position(|s| sentence.get(3..6) == Some(*s)) {
+            Some(field) => self.nmea_output[field],
+            None if sentence.get(3..6) == Some("ZDA") => self.nmea_output[ZDA_FIELD],
+            None => 1,
+        }
+    }
+
+    /// Queues the sentences for the next fix.
+    fn next_fix(&mut self) {
+        let fix = self.fix;
+        self.fix = self.fix.wrapping_add(1);
+        let sentences: Vec<String> = self.sentences.iter()
+            .filter(|s| {
+                let frequency = self.frequency(s);
+                frequency > 0 && fix.is_multiple_of(frequency as u32)
+            })
+            .cloned()
+            .collect();
+        for sentence in sentences {
+            self.output.extend(sentence.bytes());
+            self.output.extend(b"\r\n");
+        }
+    }
+
+    /// True if a fix's sentences fit in the time between fixes at the baud rate.
+    fn fits_baud_rate(&self, update_rate: u32) -> bool {
+        let bytes_per_fix: u32 = self.sentences.iter()
+            .map(|s| self.frequency(s))
+            .filter(|frequency| *frequency > 0)
+            .map(|frequency| SENTENCE_BYTES / frequency as u32)
+            .sum();
+        // 10 bits a byte with the start and stop bits.
+        bytes_per_fix * 1000 / update_rate <= self.baud_rate / 10
+    }
+
+    /// Queues a reply: no $ and no *checksum.
+    fn reply(&mut self, sentence: &str) {
+        self.output.extend(add_checksum(sentence).bytes());
+    }
+
+    fn ack(&mut self, cmd: &str, flag: u8) {
+        self.reply(&format!("PMTK001,{},{}", cmd, flag));
+    }
+
+    /// PMTK011 and PMTK010 as given by the module when it starts up.
+    fn restart(&mut self) {
+        self.output.clear();
+        self.fix = 0;
+        self.reply("PMTK011,MTKGPS");
+        self.reply("PMTK010,001");
+    }
+
+    /// Does a command and queues the reply.
+    fn command(&mut self, line: &str) {
+        if !is_valid_checksum(line) {
+            return; // The module ignores broken commands.
+        }
+        let line = line.trim();
+        let body = &line[1..line.len() - 3];
+        self.commands.push(body.to_string());
+
+        let args: Vec<&str> = body.split(',').collect();
+        let cmd = match args[0].strip_prefix("PMTK") {
+            Some(cmd) => cmd,
+            None => return,
+        };
+        let arg = |i: usize| args.get(i).copied().unwrap_or("");
+        match cmd {
+            "101" | "102" | "103" => self.restart(),
+            "104" => {
+                self.factory_settings();
+                self.restart();
+            }
+            "220" => match arg(1).parse::<u32>() {
+                Ok(rate) if (100..=10_000).contains(&rate) && self.fits_baud_rate(rate) => {
+                    self.update_rate = rate;
+                    self.ack(cmd, 3);
+                }
+                _ => self.ack(cmd, 2),
+            },
+            "251" => {
+                // No ack, the module just changes baud rate.
+                if let Ok(baud_rate) = arg(1).parse::<u32>() {
+                    self.baud_rate = if baud_rate == 0 { 9600 } else { baud_rate };
+                }
+            }
+            "301" => match arg(1).parse::<u8>() {
+                Ok(mode) if mode <= 2 => {
+                    self.dgps_mode = mode;
+                    self.ack(cmd, 3);
+                }
+                _ => self.ack(cmd, 2),
+            },
+            "401" => self.reply(&format!("PMTK501,{}", self.dgps_mode)),
+            "313" => {
+                self.sbas_enabled = arg(1) == "1";
+                self.ack(cmd, 3);
+            }
+            "413" => self.reply(&format!("PMTK513,{}", self.sbas_enabled as u8)),
+            "314" => {
+                if arg(1) == "-1" {
+                    self.nmea_output = DEFAULT_NMEA_OUTPUT;
+                } else {
+                    let mut output = [0; 19];
+                    for (field, value) in args[1..].iter().take(19).enumerate() {
+                        output[field] = value.parse().unwrap_or(0);
+                    }
+                    self.nmea_output = output;
+                }
+                self.ack(cmd, 3);
+            }
+            "414" => {
+                let fields: Vec<String> = self.nmea_output.iter().map(|f| f.to_string()).collect();
+                self.reply(&format!("PMTK514,{}", fields.join(",")));
+            }
+            "319" => {
+                self.sbas_mode = if arg(1) == "0" { 0 } else { 1 };
+                self.ack(cmd, 3);
+                self.restart();
+            }
+            "419" => self.reply(&format!("PMTK519,{}", self.sbas_mode)),
+            "605" => self.reply(&format!("PMTK705,{}", self.release)),
+            "607" => self.reply("PMTK707,0,0,0,0,0,0,0,0,0"),
+            "127" => {
+                for _ in 0..3 {
+                    self.reply("CLR,EPO,000a8000");
+                }
+                self.ack(cmd, 3);
+            }
+            "386" | "397" => match arg(1).parse::<f32>() {
+                Ok(threshold) if (0.0..=2.0).contains(&threshold) => {
+                    self.nav_threshold = threshold;
+                    self.ack(cmd, 3);
+                }
+                _ => self.ack(cmd, 2),
+            },
+            "447" => self.reply(&format!("PMTK527,{:.2}", self.nav_threshold)),
+            "869" => match arg(1) {
+                "0" => self.reply(&format!("PMTK869,2,{},3", self.easy_enabled as u8)),
+                "1" => {
+                    self.easy_enabled = arg(2) == "1";
+                    self.ack(cmd, 3);
+                }
+                _ => self.ack(cmd, 2),
+            },
+            "330" => match arg(1).parse::<u16>() {
+                Ok(datum) if datum <= 222 => {
+                    self.datum = datum;
+                    self.ack(cmd, 3);
+                }
+                _ => self.ack(cmd, 2),
+            },
+            "430" => self.reply(&format!("PMTK530,{}", self.datum)),
+            "161" | "187" | "223" | "225" | "286" | "351" | "352" => self.ack(cmd, 3),
+            _ => self.ack(cmd, 0),
+        }
+    }
+}
+
+impl GpsPort for GpsEmulator {
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<usize, PortError> {
+        if self.output.is_empty() {
+            self.next_fix();
+        }
+        if self.output.is_empty() {
+            return Err(PortError::TimedOut);
+        }
+        let mut size = 0;
+        while size < buf.len() {
+            match self.output.pop_front() {
+                Some(byte) if self.port_baud_rate == self.baud_rate => buf[size] = byte,
+                Some(byte) => buf[size] = byte | 0x80, // Wrong baud rate reads as garbage.
+                None => break,
+            }
+            size += 1;
+        }
+        Ok(size)
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), PortError> {
+        if self.port_baud_rate != self.baud_rate {
+            return Ok(()); // The module can't make sense of it.
+        }
+        for byte in bytes {
+            self.input.push(*byte);
+            if *byte == b'\n' {
+                let line = String::from_utf8_lossy(&self.input).to_string();
+                self.input.clear();
+                self.command(&line);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod emulator_test {
+    use crate::{Gps, GpsSentence};
+    use crate::pmtk::send_pmtk::{NmeaOutput, Pmtk001Ack};
+
+    use super::GpsEmulator;
+
+    #[test]
+    fn streams_default_output() {
+        let mut gps = Gps::from_port(GpsEmulator::new());
+        // GSV is every 5th fix, so the first fix has it.
+        let mut sentences = Vec::new();
+        for _ in 0..5 {
+            sentences.push(gps.update());
+        }
+        assert!(matches!(sentences[0], GpsSentence::GGA(_, _)));
+        assert!(matches!(sentences[1], GpsSentence::GSA(_, _)));
+        assert!(matches!(&sentences[2], GpsSentence::GSV(_, gsv) if gsv.satellites.len() == 6));
+        assert!(matches!(sentences[3], GpsSentence::RMC(_, _)));
+        assert!(matches!(sentences[4], GpsSentence::VTG(_, _)));
+        // No GLL or GSV in the second fix.
+        assert!(matches!(gps.update(), GpsSentence::GGA(_, _)));
+        assert!(matches!(gps.update(), GpsSentence::GSA(_, _)));
+        assert!(matches!(gps.update(), GpsSentence::RMC(_, _)));
+    }
+
+    #[test]
+    fn nmea_output_changes_stream() {
+        let mut gps = Gps::from_port(GpsEmulator::new());
+        let output = NmeaOutput { gll: 1, rmc: 0, vtg: 0, gga: 0, gsa: 0, gsv: 0, zda: 1, pmtkchn_interval: 0 };
+        assert_eq!(gps.pmtk_314_api_set_nmea_output(output), Pmtk001Ack::Success);
+        for _ in 0..3 {
+            assert!(matches!(gps.update(), GpsSentence::GLL(_, _)));
+            match gps.update() {
+                GpsSentence::ZDA(_, zda) => assert_eq!(zda.date.map(|date| (date.day, date.month, date.year)), Some((11, 5, 2020))),
+                other => panic!("Expected ZDA, got {:?}", other),
+            }
+        }
+        assert_eq!(gps.port.commands, vec!["PMTK314,1,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,1,0"]);
+    }
+
+    #[test]
+    fn update_rate_limited_by_baud_rate() {
+        let mut gps = Gps::from_port(GpsEmulator::new());
+        assert_eq!(gps.pmtk_220_set_nmea_updaterate("200"), Pmtk001Ack::Failed);
+        gps.port.baud_rate = 57600;
+        gps.port.port_baud_rate = 57600;
+        assert_eq!(gps.pmtk_220_set_nmea_updaterate("200"), Pmtk001Ack::Success);
+        assert_eq!(gps.port.update_rate, 200);
+    }
+
+    #[test]
+    fn baud_rate_change() {
+        let mut gps = Gps::from_port(GpsEmulator::new());
+        gps.send_command("PMTK251,115200");
+        assert_eq!(gps.port.baud_rate, 115200);
+        assert_eq!(gps.update(), GpsSentence::InvalidBytes);
+        // Commands at the old baud rate are lost.
+        assert_eq!(gps.pmtk_220_set_nmea_updaterate("1000"), Pmtk001Ack::NoPacket);
+        gps.port.port_baud_rate = 115200;
+        assert_eq!(gps.pmtk_220_set_nmea_updaterate("1000"), Pmtk001Ack::Success);
+    }
+
+    #[test]
+    fn unknown_command_is_invalid() {
+        let mut gps = Gps::from_port(GpsEmulator::new());
+        gps.send_command("PMTK999");
+        assert_eq!(gps.pmtk_001(10), Pmtk001Ack::Invalid);
+    }
+}
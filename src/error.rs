@@ -0,0 +1,114 @@
+//! # Error
+//! Errors from opening the port to the gps and from the file helpers.
+
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io;
+
+use crate::open_gps::gps::PortError;
+
+/// Everything that can go wrong opening, reading or writing the gps port and files.
+///
+/// - PortOpen -> The serial port couldn't be opened, eg the usb adapter is unplugged.
+/// - BadBaudRate -> The baud rate given isn't a number.
+/// - TimedOut -> Nothing came from the gps in time.
+/// - Io -> Reading or writing the port or a file failed.
+#[derive(Debug)]
+pub enum Error {
+    #[cfg(feature = "std")]
+    PortOpen(serialport::Error),
+    BadBaudRate,
+    TimedOut,
+    #[cfg(feature = "std")]
+    Io(io::Error),
+    /// Reading or writing the port failed, without std there is no io::Error to give.
+    Port,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            Error::PortOpen(e) => write!(f, "Port could not be opened: {}", e),
+            Error::BadBaudRate => write!(f, "Baud rate is not a number"),
+            Error::TimedOut => write!(f, "Timed out waiting for the gps"),
+            #[cfg(feature = "std")]
+            Error::Io(e) => write!(f, "Io error: {}", e),
+            Error::Port => write!(f, "Port error"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::PortOpen(e) => Some(e),
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<PortError> for Error {
+    fn from(e: PortError) -> Error {
+        match e {
+            PortError::TimedOut => Error::TimedOut,
+            PortError::Other => Error::Port,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        match e.kind() {
+            io::ErrorKind::TimedOut => Error::TimedOut,
+            _ => Error::Io(e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<serialport::Error> for Error {
+    fn from(e: serialport::Error) -> Error {
+        Error::PortOpen(e)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<bincode::Error> for Error {
+    fn from(e: bincode::Error) -> Error {
+        match *e {
+            bincode::ErrorKind::Io(e) => Error::from(e),
+            e => Error::Io(io::Error::new(io::ErrorKind::InvalidData, e)),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod error_test {
+    use std::io;
+
+    use crate::{Error, Gps, GpsSentence};
+    use crate::open_gps::gps::PortError;
+
+    #[test]
+    fn bad_baud_rate() {
+        assert!(matches!(Gps::new("/dev/serial0", "96OO"), Err(Error::BadBaudRate)));
+    }
+
+    #[test]
+    fn missing_file() {
+        match GpsSentence::read_from("no_such_gps_file") {
+            Err(Error::Io(e)) => assert_eq!(e.kind(), io::ErrorKind::NotFound),
+            other => panic!("Expected a not found Io error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn timeouts() {
+        assert!(matches!(Error::from(io::Error::from(io::ErrorKind::TimedOut)), Error::TimedOut));
+        assert!(matches!(Error::from(PortError::TimedOut), Error::TimedOut));
+    }
+}
@@ -0,0 +1,187 @@
+//! # Framer
+//! Splits a stream of bytes from the gps into sentences, without doing any reading itself.
+//!
+//! Give NmeaFramer whatever bytes there are, as they arrive: a whole DMA buffer, a single byte
+//! from a uart interrupt or a chunk of a log file. Every complete sentence with a valid checksum
+//! comes back out, without the line ending.
+//!
+//! - A $ always starts a new sentence, so the framer picks the stream up again after garbage.
+//! - A sentence ends at \r or \n, so a missing \r is fine. A sentence followed straight away by
+//!   the next $ is also kept if its checksum is valid.
+//! - Partial sentences are kept between feeds.
+//! - Lines with a bad checksum, bytes that aren't printable ascii or that are too long are dropped.
+//! ```ignore
+//! let mut framer = NmeaFramer::new();
+//! for sentence in framer.feed(&dma_buffer[..size]) {
+//!     match GpsSentence::from_sentence(&sentence) {
+//!         GpsSentence::GGA(_, gga) => ...,
+//!         _ => (),
+//!     }
+//! }
+//! ```
+
+use heapless::{String, Vec};
+
+use crate::open_gps::gps::{is_valid_checksum, MAX_SENTENCE_LENGTH};
+
+/// Frames sentences from bytes pushed into it.
+pub struct NmeaFramer {
+    line: Vec<u8, MAX_SENTENCE_LENGTH>,
+    in_sentence: bool,
+}
+
+impl Default for NmeaFramer {
+    fn default() -> NmeaFramer {
+        NmeaFramer::new()
+    }
+}
+
+impl NmeaFramer {
+    pub fn new() -> NmeaFramer {
+        NmeaFramer { line: Vec::new(), in_sentence: false }
+    }
+
+    /// Gives an iterator over the sentences finished by the bytes.
+    ///
+    /// Bytes after the last finished sentence are kept for the next feed. Bytes not read from the
+    /// NmeaStream when it's dropped are lost.
+    pub fn feed<'a>(&'a mut self, bytes: &'a [u8]) -> NmeaStream<'a> {
+        NmeaStream { framer: self, bytes }
+    }
+
+    /// Pushes a single byte, giving the sentence it finishes if there is one.
+    pub fn push(&mut self, byte: u8) -> Option<String<MAX_SENTENCE_LENGTH>> {
+        match byte {
+            b'$' => {
+                let finished = self.finish();
+                self.in_sentence = true;
+                let _ = self.line.push(byte);
+                finished
+            }
+            b'\r' | b'\n' => self.finish(),
+            _ if !self.in_sentence => None,
+            b' '..=b'~' => {
+                if self.line.push(byte).is_err() {
+                    self.reset(); // Too long, wait for the next $.
+                }
+                None
+            }
+            _ => {
+                self.reset(); // Not a byte an NMEA sentence can have.
+                None
+            }
+        }
+    }
+
+    /// Drops the partial sentence.
+    pub fn reset(&mut self) {
+        self.line.clear();
+        self.in_sentence = false;
+    }
+
+    /// Ends the current sentence, giving it if the checksum is valid.
+    fn finish(&mut self) -> Option<String<MAX_SENTENCE_LENGTH>> {
+        if !self.in_sentence {
+            return None;
+        }
+        let line = core::mem::take(&mut self.line);
+        self.in_sentence = false;
+        // Only printable ascii gets into the line, so it's valid utf8.
+        let sentence = String::from_utf8(line).ok()?;
+        if is_valid_checksum(sentence.as_str()) {
+            Some(sentence)
+        } else {
+            None
+        }
+    }
+}
+
+/// The sentences finished by the bytes given to NmeaFramer::feed.
+pub struct NmeaStream<'a> {
+    framer: &'a mut NmeaFramer,
+    bytes: &'a [u8],
+}
+
+impl<'a> Iterator for NmeaStream<'a> {
+    type Item = String<MAX_SENTENCE_LENGTH>;
+
+    fn next(&mut self) -> Option<String<MAX_SENTENCE_LENGTH>> {
+        while let Some((byte, rest)) = self.bytes.split_first() {
+            self.bytes = rest;
+            if let Some(sentence) = self.framer.push(*byte) {
+                return Some(sentence);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod framer_test {
+    use crate::GpsSentence;
+
+    use super::NmeaFramer;
+
+    const GGA: &str = "$GNGGA,131613.000,5132.7314,N,00005.9099,W,1,9,1.17,42.4,M,47.0,M,,*60";
+    const VTG: &str = "$GPVTG,89.68,T,,M,0.00,N,0.0,K*5F";
+
+    fn frame(framer: &mut NmeaFramer, bytes: &str) -> Vec<String> {
+        framer.feed(bytes.as_bytes()).map(|sentence| sentence.as_str().to_string()).collect()
+    }
+
+    #[test]
+    fn whole_sentences() {
+        let mut framer = NmeaFramer::new();
+        assert_eq!(frame(&mut framer, &format!("{}\r\n{}\r\n", GGA, VTG)), vec![GGA, VTG]);
+    }
+
+    #[test]
+    fn sentence_across_feeds() {
+        let mut framer = NmeaFramer::new();
+        assert!(frame(&mut framer, &GGA[..20]).is_empty());
+        assert!(frame(&mut framer, &GGA[20..]).is_empty());
+        assert_eq!(frame(&mut framer, "\r\n"), vec![GGA]);
+    }
+
+    #[test]
+    fn byte_at_a_time() {
+        let mut framer = NmeaFramer::new();
+        let mut sentences = Vec::new();
+        for byte in format!("{}\r\n", VTG).bytes() {
+            sentences.extend(framer.push(byte));
+        }
+        assert_eq!(sentences.len(), 1);
+        assert_eq!(sentences[0].as_str(), VTG);
+    }
+
+    #[test]
+    fn garbage_and_resync() {
+        let mut framer = NmeaFramer::new();
+        let bytes = format!("\u{0}\u{ff}junk*12\r\n$GPGGA,1234{}\n\u{7}$GPVTG,cut\r\n{}\n", GGA, VTG);
+        assert_eq!(frame(&mut framer, &bytes), vec![GGA, VTG]);
+    }
+
+    #[test]
+    fn missing_line_endings() {
+        let mut framer = NmeaFramer::new();
+        assert_eq!(frame(&mut framer, &format!("{}{}\n", GGA, VTG)), vec![GGA, VTG]);
+    }
+
+    #[test]
+    fn bad_checksum_and_too_long() {
+        let mut framer = NmeaFramer::new();
+        let long = format!("${}*00\r\n", "A".repeat(300));
+        let bad = "$GPVTG,89.68,T,,M,0.00,N,0.0,K*5E\r\n";
+        assert_eq!(frame(&mut framer, &format!("{}{}{}\r\n", long, bad, VTG)), vec![VTG]);
+    }
+
+    #[test]
+    fn framed_sentence_parses() {
+        let mut framer = NmeaFramer::new();
+        let sentence = framer.feed(format!("{}\r\n", GGA).as_bytes()).next().unwrap();
+        match GpsSentence::from_sentence(&sentence) {
+            GpsSentence::GGA(_, gga) => assert_eq!(gga.satellites_used, 9),
+            other => panic!("Expected GGA, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,249 @@
+//! # I2c
+//! The Adafruit Mini GPS PA1010D also talks I2C, at address 0x10.
+//!
+//! Every read from the gps gives a 255 byte chunk. When the gps has no more data to give, the rest
+//! of the chunk is padded with \n (0x0A) filler. I2cPort strips the filler and hands the bytes
+//! on to Gps, so sentences split across chunks are put back together by read_line as normal.
+//! Commands are written to the same address.
+//!
+//! Any embedded-hal I2c bus works. On linux, LinuxI2c opens /dev/i2c-*.
+//! ```ignore
+//! let i2c = LinuxI2c::open("/dev/i2c-1").unwrap();
+//! let mut gps = Gps::from_i2c(i2c);
+//! gps.pmtk_220_set_nmea_updaterate("1000");
+//! let sentence = gps.update();
+//! ```
+
+use embedded_hal::i2c::I2c;
+
+use crate::open_gps::gps::{Gps, GpsPort, PortError};
+
+/// I2C address of the PA1010D.
+pub const PA1010D_ADDRESS: u8 = 0x10;
+
+/// Size of the chunk the gps gives for every read.
+pub const I2C_CHUNK_SIZE: usize = 255;
+
+/// The gps pads a chunk with \n when it has nothing left to give.
+const FILLER: u8 = b'\n';
+
+/// An I2c bus as a GpsPort.
+///
+/// A chunk that is only filler is an empty read. After max_empty_reads of them in a row, reading
+/// gives up with PortError::TimedOut. One chunk takes about 25 ms at 100 kHz, so the default of 40 is
+/// about a second.
+pub struct I2cPort<I> {
+    pub i2c: I,
+    pub address: u8,
+    pub max_empty_reads: u32,
+    chunk: [u8; I2C_CHUNK_SIZE],
+    start: usize,
+    end: usize,
+    last_byte: u8,
+}
+
+impl<I: I2c> I2cPort<I> {
+    /// I2cPort to the PA1010D at its default address.
+    pub fn new(i2c: I) -> I2cPort<I> {
+        I2cPort::with_address(i2c, PA1010D_ADDRESS)
+    }
+
+    pub fn with_address(i2c: I, address: u8) -> I2cPort<I> {
+        I2cPort {
+            i2c,
+            address,
+            max_empty_reads: 40,
+            chunk: [0; I2C_CHUNK_SIZE],
+            start: 0,
+            end: 0,
+            last_byte: 0,
+        }
+    }
+
+    /// Reads chunks until one has data in it. The filler is left in, it's skipped in read_bytes.
+    fn fill_chunk(&mut self) -> Result<(), PortError> {
+        for _read in 0..self.max_empty_reads {
+            self.i2c.read(self.address, &mut self.chunk).map_err(|_e| PortError::Other)?;
+            self.start = 0;
+            self.end = I2C_CHUNK_SIZE;
+            if self.chunk.iter().any(|byte| *byte != FILLER) {
+                return Ok(());
+            }
+            self.end = 0;
+        }
+        Err(PortError::TimedOut)
+    }
+}
+
+impl<I: I2c> GpsPort for I2cPort<I> {
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<usize, PortError> {
+        let mut size = 0;
+        while size < buf.len() {
+            if self.start == self.end {
+                if size > 0 {
+                    break; // Give what there is before reading another chunk.
+                }
+                self.fill_chunk()?;
+            }
+            let byte = self.chunk[self.start];
+            self.start += 1;
+            // A \n only ends a sentence after a \r, any other \n is filler.
+            if byte == FILLER && self.last_byte != b'\r' {
+                continue;
+            }
+            self.last_byte = byte;
+            buf[size] = byte;
+            size += 1;
+        }
+        Ok(size)
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), PortError> {
+        for chunk in bytes.chunks(I2C_CHUNK_SIZE) {
+            self.i2c.write(self.address, chunk).map_err(|_e| PortError::Other)?;
+        }
+        Ok(())
+    }
+}
+
+impl<I: I2c> Gps<I2cPort<I>> {
+    /// Gives a Gps reading from and writing to a PA1010D on an I2c bus.
+    pub fn from_i2c(i2c: I) -> Gps<I2cPort<I>> {
+        Gps::from_port(I2cPort::new(i2c))
+    }
+}
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub mod linux {
+    //! Linux /dev/i2c-* bus.
+    use std::fs::{File, OpenOptions};
+    use std::io::{self, Read, Write};
+    use std::os::unix::io::AsRawFd;
+
+    use embedded_hal::i2c::{self, ErrorType, I2c, Operation, SevenBitAddress};
+
+    /// ioctl to set the address of the device the file talks to.
+    const I2C_SLAVE: u16 = 0x0703;
+
+    /// Failed read, write or address change on the linux bus.
+    #[derive(Debug)]
+    pub struct LinuxI2cError(pub io::Error);
+
+    impl i2c::Error for LinuxI2cError {
+        fn kind(&self) -> i2c::ErrorKind {
+            i2c::ErrorKind::Other
+        }
+    }
+
+    /// A linux I2c bus, eg /dev/i2c-1 on a raspberry pi.
+    pub struct LinuxI2c {
+        file: File,
+        address: Option<u8>,
+    }
+
+    impl LinuxI2c {
+        pub fn open(path: &str) -> io::Result<LinuxI2c> {
+            let file = OpenOptions::new().read(true).write(true).open(path)?;
+            Ok(LinuxI2c { file, address: None })
+        }
+
+        fn set_address(&mut self, address: u8) -> io::Result<()> {
+            if self.address == Some(address) {
+                return Ok(());
+            }
+            let r = unsafe { libc::ioctl(self.file.as_raw_fd(), I2C_SLAVE as _, address as libc::c_ulong) };
+            if r < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            self.address = Some(address);
+            Ok(())
+        }
+    }
+
+    impl ErrorType for LinuxI2c {
+        type Error = LinuxI2cError;
+    }
+
+    impl I2c<SevenBitAddress> for LinuxI2c {
+        fn transaction(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<(), LinuxI2cError> {
+            self.set_address(address).map_err(LinuxI2cError)?;
+            for operation in operations {
+                match operation {
+                    Operation::Read(buf) => self.file.read_exact(buf).map_err(LinuxI2cError)?,
+                    Operation::Write(bytes) => self.file.write_all(bytes).map_err(LinuxI2cError)?,
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod i2c_test {
+    use std::collections::VecDeque;
+
+    use embedded_hal::i2c::{ErrorKind, ErrorType, I2c, Operation};
+
+    use crate::{Gps, GpsSentence};
+    use crate::pmtk::send_pmtk::Pmtk001Ack;
+
+    use super::{I2C_CHUNK_SIZE, PA1010D_ADDRESS};
+
+    /// Mock I2c bus: gives the scripted chunks padded with \n, then chunks of only filler.
+    /// Records every write.
+    struct MockI2c {
+        chunks: VecDeque<Vec<u8>>,
+        writes: Vec<(u8, Vec<u8>)>,
+    }
+
+    impl MockI2c {
+        fn new(chunks: &[&str]) -> MockI2c {
+            MockI2c { chunks: chunks.iter().map(|c| c.as_bytes().to_vec()).collect(), writes: Vec::new() }
+        }
+    }
+
+    impl ErrorType for MockI2c {
+        type Error = ErrorKind;
+    }
+
+    impl I2c for MockI2c {
+        fn transaction(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+            assert_eq!(address, PA1010D_ADDRESS);
+            for operation in operations {
+                match operation {
+                    Operation::Read(buf) => {
+                        assert_eq!(buf.len(), I2C_CHUNK_SIZE);
+                        let chunk = self.chunks.pop_front().unwrap_or_default();
+                        for (i, byte) in buf.iter_mut().enumerate() {
+                            *byte = *chunk.get(i).unwrap_or(&b'\n');
+                        }
+                    }
+                    Operation::Write(bytes) => self.writes.push((address, bytes.to_vec())),
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn sentence_across_chunks() {
+        let mut gps = Gps::from_i2c(MockI2c::new(&[
+            "\n\n$GNGGA,131613.000,5132.7314,N,00005.9099,",
+            "",
+            "W,1,9,1.17,42.4,M,47.0,M,,*60\r\n$GPVTG,",
+        ]));
+        match gps.update() {
+            GpsSentence::GGA(_, gga) => assert_eq!(gga.satellites_used, 9),
+            other => panic!("Expected GGA, got {:?}", other),
+        }
+        // The VTG is cut off by filler and never finished.
+        assert_eq!(gps.update(), GpsSentence::NoConnection);
+    }
+
+    #[test]
+    fn command_over_i2c() {
+        let mut gps = Gps::from_i2c(MockI2c::new(&["$PMTK001,220,3*30\r\n"]));
+        assert_eq!(gps.pmtk_220_set_nmea_updaterate("1000"), Pmtk001Ack::Success);
+        assert_eq!(gps.port.i2c.writes, vec![(PA1010D_ADDRESS, b"$PMTK220,1000*1F\r\n".to_vec())]);
+    }
+}
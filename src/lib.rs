@@ -20,19 +20,46 @@
 //! If increasing the update rate, the baud rate may also need to be increased.
 //! A rule of thumb is, one sentence is 256 bytes -> at 9600 baud rate, 37.5 sentences per second.
 //!
+//! Reading waits up to a second for each sentence. At 10Hz and 115200 baud, or in periodic and
+//! AlwaysLocate modes where the gps is quiet for a long time, set the time outs with
+//! [GpsOptions](struct.GpsOptions.html): Gps::new_with_options(port, baud_rate, options) or
+//! Gps::from_port(port).with_options(options).
+//!
 //! # Module Outputs
 //! gps.update() outputs a GpsSentence enum which mostly gives other structs for different sentence types
 //!
-//! GpsSentence enum types:
+//! GpsSentence enum types, each with the [Talker](enum.Talker.html) (GP, GL, GA, GB, GN, QZ) that gave it:
 //! - GGA(GgaData) -> [GgaData](nmea/gga/struct.GgaData.html): Latitude, Longitude, Position fix, Satellites seen, HDOP, altitude, Geoidal Seperation, Age of difference correction.
 //! - VTG(VtgData) -> [VtgData](nmea/vtg/struct.VtgData.html): Course (true), Course (magnetic), speed knots, speed kph.
 //! - GSA(GsaData) -> [GsaData](nmea/gsa/struct.GsaData.html): List of satellites used, PDOP, HDOP, VDOP.
-//! - GSV(Vec<Satellites>) -> [Satellites](nmea/gsv/struct.Satellites.html): Satellites in view data: sat id, elevation, azimuth and SNR for each sat seen.
+//!   Satellites in GSA and GSV are a [SatelliteId](struct.SatelliteId.html), their constellation and PRN.
+//! - GSV(GsvSet) -> [GsvData](nmea/gsv/struct.GsvData.html), boxed with std: Satellites in view, and sat id, elevation, azimuth and SNR for each sat seen. Given once every message of the set is read.
 //! - GLL(GllData) -> [GllData](nmea/gll/struct.GllData.html): Latitude, Longitude only.
 //! - RMC(RmcData) -> [RmcData](nmea/rmc/struct.RmcData.html): UTC, Latitude, Longitude, speed, course, date, magnetic variation.
+//! - GNS(GnsData) -> [GnsData](nmea/gns/struct.GnsData.html): UTC, Latitude, Longitude, mode of each constellation, satellites used, HDOP, altitude, Geoidal Seperation, NMEA 4.1 nav status.
+//! - GST(GstData) -> [GstData](nmea/gst/struct.GstData.html): UTC, RMS, error ellipse and Latitude, Longitude, altitude standard deviations in metres.
+//! - ZDA(ZdaData) -> [ZdaData](nmea/zda/struct.ZdaData.html): UTC, day, month, 4 digit year, local time zone.
+//! - PMTK001(u16, Pmtk001Ack) -> Ack for a command, with the command's packet type. Acks that come late are not lost.
+//! - PMTK010(SystemMessage) -> Startup, EPO data needs updating, back to normal mode.
+//! - PMTK011(String) -> Text message, "MTKGPS" when the gps starts up.
+//! - PMTK(u16, String) -> Any other PMTK reply: the packet type and its fields.
 //! - NoConnection -> The gps is not connected, no bytes are being received
 //! - InvalidBytes -> Bytes being received are not valid, probably port baud rate and gps baud rate mismatch
+//! - Unknown { header, fields } -> A sentence with a valid checksum that isn't parsed, eg $PGTOP. Parse your own with [SentenceParsers](struct.SentenceParsers.html).
 //! - InvalidSentence -> Sentence outputted has incorrect checksum, the sentence was probably incomplete.
+//! - ParseError(NmeaError) -> The checksum is fine but a field couldn't be read: a missing field, a
+//!   bad number, a bad compass direction. See [NmeaError](enum.NmeaError.html).
+//!
+//! Times are [NmeaTime](struct.NmeaTime.html) and dates [NmeaDate](struct.NmeaDate.html).
+//! RmcData::datetime and ZdaData::datetime put them together as an
+//! [NmeaDateTime](struct.NmeaDateTime.html). With the chrono or time feature they convert to
+//! chrono and time types.
+//!
+//! ## Writing NMEA
+//! The data structs write themselves back into NMEA sentences with their checksum, eg
+//! GgaData::to_sentence(Talker::Gps), to forward a fix to a chart plotter or autopilot.
+//! GpsSentence::to_sentences does the same for any sentence, eg to turn saved GpsSentences back
+//! into an NMEA file. Build other sentences with [SentenceWriter](struct.SentenceWriter.html).
 //!
 //! # Some technical information
 //! ## Dilution of precision
@@ -62,26 +89,95 @@
 //! by the gps is, in my opinion, not overly useful for precise elevation, but rather is useful in
 //! measuring the difference in height between objects.
 //!
+//! ## no_std
+//! The nmea parsers, checksums and the PMTK command layer do not need std or an allocator.
+//! Turn off default features to use them on a microcontroller:
+//! `adafruit_gps = {version = "0.4", default-features = false}`.
+//! The port given to Gps then has to implement [GpsPort](trait.GpsPort.html) and
+//! return `PortError::TimedOut` when no bytes arrive.
+//!
+//! The default `std` feature adds the serial port, file saving and bincode.
+//!
+//! On a microcontroller, give the Gps an embedded-io or embedded-hal-nb uart with
+//! Gps::from_io_uart(uart) or Gps::from_nb_uart(uart, max_polls). See the uart module.
+//!
+//! The PA1010D can also be read over I2C with Gps::from_i2c(i2c), from any embedded-hal I2c bus or
+//! LinuxI2c::open("/dev/i2c-1"). See the i2c module.
+//!
+//! ## Framing sentences yourself
+//! NmeaFramer splits bytes from anywhere (a uart interrupt, a DMA buffer, a log file) into
+//! checksum-valid sentences, and GpsSentence::from_sentence parses them. See the framer module.
+//!
+//! ## Testing without a gps
+//! GpsEmulator is a software PMTK module: Gps::from_port(GpsEmulator::new()) streams NMEA and
+//! answers PMTK commands like the real thing. See the emulator module.
+//!
+//! ReplayPort plays a raw NMEA capture back through Gps::update(), as fast as possible or in real
+//! time, optionally looping. See the replay module.
+//!
+//! ## Async
+//! AsyncGps has async update, send_command and the same PMTK commands as Gps for any
+//! embedded-io-async port, eg under embassy. It reads with GpsOptions too. The `tokio` feature adds TokioPort and AsyncGps::from_tokio(stream) for tokio
+//! serial ports, pipes and TCP streams.
+//!
 //! ## Saving data
 //! GpsSentence types can be written and read to a bytes file using the the append_to() and read_from()
 //! traits: See examples/example_io.rs for details.
 //! ```
-//! use adafruit_gps::GpsSentence;
+//! use adafruit_gps::{GpsSentence, Talker};
 //! use adafruit_gps::gga::GgaData;
-//! let data: Vec<GpsSentence> = GpsSentence::read_from("file"); // Read from file
+//! # fn main() -> Result<(), adafruit_gps::Error> {
+//! GpsSentence::GGA(Talker::Gps, GgaData::default()).append_to("file")?; // Append a single item to a file
 //!
-//! GpsSentence::GGA(GgaData::default()).append_to("file"); // Append a single item to a file
+//! let data: Vec<GpsSentence> = GpsSentence::read_from("file")?; // Read from file
+//! # std::fs::remove_file("file")?;
+//! # Ok(())
+//! # }
 //! ```
 //!
+//! ## Errors
+//! Gps::new, open_port and the file helpers give an [Error](enum.Error.html) instead of panicking:
+//! the port couldn't be opened, the baud rate isn't a number, a time out, or an io error.
+//!
 //!
 //!
 
+#![cfg_attr(not(feature = "std"), no_std)]
 
-pub use crate::nmea::{gga, gll, gsa, gsv, rmc, vtg};
-pub use crate::open_gps::gps::{Gps, GpsSentence};
-pub use crate::pmtk::send_pmtk::{set_baud_rate, NmeaOutput};
+pub use crate::error::Error;
+pub use crate::nmea::{gga, gll, gns, gsa, gst, gsv, rmc, vtg, zda};
+pub use crate::nmea::datetime::{NmeaDate, NmeaDateTime, NmeaTime};
+pub use crate::nmea::satellite::{Constellation, SatelliteId};
+pub use crate::nmea::talker::Talker;
+pub use crate::nmea::parse_nmea::{Fields, NmeaError, parse_sentence};
+pub use crate::nmea::write_nmea::SentenceWriter;
+pub use crate::open_gps::gps::{Gps, GpsOptions, GpsPort, GpsSentence, GsvSet, PortError, SentenceParser, SentenceParsers};
+pub use crate::pmtk::send_pmtk::{NmeaOutput, Pmtk001Ack, SystemMessage};
+#[cfg(feature = "std")]
+pub use crate::pmtk::send_pmtk::set_baud_rate;
+pub use crate::uart::{IoUart, NbUart};
+pub use crate::async_gps::AsyncGps;
+pub use crate::framer::{NmeaFramer, NmeaStream};
+#[cfg(feature = "std")]
+pub use crate::emulator::GpsEmulator;
+#[cfg(feature = "std")]
+pub use crate::replay::{read_capture, ReplayPort};
+#[cfg(feature = "tokio")]
+pub use crate::async_gps::tokio_port::TokioPort;
+pub use crate::i2c::{I2cPort, PA1010D_ADDRESS};
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub use crate::i2c::linux::LinuxI2c;
 
+mod error;
 mod nmea;
 mod pmtk;
 mod open_gps;
+mod uart;
+mod i2c;
+mod async_gps;
+mod framer;
+#[cfg(feature = "std")]
+mod emulator;
+#[cfg(feature = "std")]
+mod replay;
 
@@ -8,6 +8,9 @@
 //! - GSV -> Satellites in view data: sat id, elevation, azimuth and SNR for each sat seen.
 //! - RMC -> UTC, Latitude, Longitude, speed, course, date, magnetic variation.
 //! - GLL -> Latitude, Longitude.
+//! - GNS -> UTC, Latitude, Longitude, mode for each constellation, sats used, HDOP, altitude, Geoidal Seperation, nav status.
+//! - GST -> UTC, RMS, error ellipse, Latitude, Longitude and altitude standard deviations.
+//! - ZDA -> UTC, day, month, year, local time zone.
 //!
 //! ## Sentence prefix: ${GP, GL, GA, GN}{GGA, GSA, GSV, RMC, VTG}
 //! GP is short for GPS (American)
@@ -27,6 +30,8 @@
 //!
 //! In the GP+GL and GP+GL+GA modes, all satellites from those systems are used for the best fix.
 //!
+//! The talker is kept with each sentence, see [Talker](talker/enum.Talker.html).
+//!
 //! ## Notes
 //! For GSA sentences, there is a sentence for each family of satellites seen (GPS, GLONASS and Galileo)
 //!, probably all in that order. It seems that the DOPs are all the same between them.
@@ -34,40 +39,219 @@
 pub mod parse_nmea {
     //! Main module for parsing any NMEA sentence and exporting NMEA parsing to lib.rs
 
+    use core::fmt;
+    use core::str::FromStr;
+
+    use serde::{Deserialize, Serialize};
+
     use crate::open_gps;
 
-    pub fn _parse_degrees(degrees: &str, compass_direction: &str) -> Option<f32> {
-        // Parse NMEA lat/long data pair dddmm.mmmm into pure degrees value.
-        // ddd is degrees, mm.mmmm is minutes
-        // NMEA format is either ddmm.mmmmm or dddmm.mmmmm
-        // Formula is ->
-        if degrees.is_empty() {
-            return None;
+    use super::talker::Talker;
+
+    /// Most fields in any PMTK reply (PMTK314 is the longest at 20).
+    pub const MAX_FIELDS: usize = 32;
+
+    /// Why a sentence couldn't be parsed. The usize is the index of the field, the header is 0.
+    ///
+    /// - MissingField -> The sentence ended before the field, or a field that is always given is empty.
+    /// - BadNumber -> The field should be a number.
+    /// - WrongSentence -> The header isn't the sentence type the parser reads, eg a GSV given to parse_gga.
+    /// - BadDirection -> The field should be N, S, E or W.
+    /// - GsvSequence -> A GSV message isn't the next one of its talker's set, or the set has more
+    ///   messages than NMEA allows.
+    #[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+    pub enum NmeaError {
+        MissingField(usize),
+        BadNumber(usize),
+        WrongSentence,
+        BadDirection(usize),
+        GsvSequence,
+    }
+
+    impl fmt::Display for NmeaError {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                NmeaError::MissingField(index) => write!(f, "Field {} is missing", index),
+                NmeaError::BadNumber(index) => write!(f, "Field {} is not a number", index),
+                NmeaError::WrongSentence => write!(f, "Wrong sentence type for the parser"),
+                NmeaError::BadDirection(index) => write!(f, "Field {} is not a compass direction", index),
+                NmeaError::GsvSequence => write!(f, "GSV message out of sequence"),
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl std::error::Error for NmeaError {}
+
+    /// The fields of a sentence, read one after another straight from the sentence without
+    /// copying or allocating.
+    ///
+    /// Each next_* reads one field, apart from next_lat and next_long that read the value and its
+    /// compass direction. Empty fields give None, reading past the end of the sentence gives
+    /// MissingField. As an Iterator it gives the fields as they are.
+    /// ```ignore
+    /// let mut fields = Fields::new("$GPVTG,89.68,T,,M,0.00,N,0.0,K");
+    /// fields.check_header("VTG")?;
+    /// let true_course: Option<f32> = fields.next_f32()?;
+    /// let reference: Option<char> = fields.next_char()?;
+    /// ```
+    #[derive(Debug, Clone)]
+    pub struct Fields<'a> {
+        rest: Option<&'a str>,
+        index: usize,
+    }
+
+    impl<'a> Fields<'a> {
+        /// The fields of a sentence without its checksum, eg "$GPVTG,89.68,T,,M,0.00,N,0.0,K".
+        pub fn new(sentence: &'a str) -> Fields<'a> {
+            Fields { rest: Some(sentence), index: 0 }
+        }
+
+        /// The index of the next field, the header is 0.
+        pub fn index(&self) -> usize {
+            self.index
+        }
+
+        /// The sentence type of the header, eg "GGA" for $GNGGA, without reading it.
+        pub fn sentence_type(&self) -> Option<&'a str> {
+            self.clone().next()?.get(3..6)
+        }
+
+        /// The talker of the header, without reading it.
+        pub fn talker(&self) -> Talker {
+            Talker::from_header(self.clone().next().unwrap_or(""))
+        }
+
+        /// Reads the header, WrongSentence if it isn't the sentence type.
+        pub fn check_header(&mut self, sentence_type: &str) -> Result<(), NmeaError> {
+            if self.next_field()?.get(3..6) == Some(sentence_type) {
+                Ok(())
+            } else {
+                Err(NmeaError::WrongSentence)
+            }
+        }
+
+        /// The next field as it is.
+        pub fn next_field(&mut self) -> Result<&'a str, NmeaError> {
+            let rest = self.rest.ok_or(NmeaError::MissingField(self.index))?;
+            let (field, rest) = match rest.split_once(',') {
+                Some((field, rest)) => (field, Some(rest)),
+                None => (rest, None),
+            };
+            self.rest = rest;
+            self.index += 1;
+            Ok(field)
+        }
+
+        /// The fields not read yet as one str, eg for free text that may have commas in it.
+        pub fn remaining(&self) -> &'a str {
+            self.rest.unwrap_or("")
+        }
+
+        /// Skips over fields that aren't used, eg units.
+        pub fn skip_fields(&mut self, count: usize) -> Result<(), NmeaError> {
+            for _field in 0..count {
+                self.next_field()?;
+            }
+            Ok(())
+        }
+
+        /// The next field as a number, None when it's empty.
+        pub fn next_number<T: FromStr>(&mut self) -> Result<Option<T>, NmeaError> {
+            let index = self.index;
+            match self.next_field()? {
+                "" => Ok(None),
+                value => value.parse().map(Some).map_err(|_e| NmeaError::BadNumber(index)),
+            }
+        }
+
+        /// The next field as a number that is always given, MissingField when it's empty.
+        pub fn next_required<T: FromStr>(&mut self) -> Result<T, NmeaError> {
+            let index = self.index;
+            self.next_number()?.ok_or(NmeaError::MissingField(index))
+        }
+
+        pub fn next_f32(&mut self) -> Result<Option<f32>, NmeaError> {
+            self.next_number()
+        }
+
+        pub fn next_f64(&mut self) -> Result<Option<f64>, NmeaError> {
+            self.next_number()
+        }
+
+        pub fn next_i32(&mut self) -> Result<Option<i32>, NmeaError> {
+            self.next_number()
+        }
+
+        /// A number newer NMEA versions add to the end of a sentence. None when it's left off, as
+        /// older versions do, or empty.
+        pub fn next_trailing<T: FromStr>(&mut self) -> Result<Option<T>, NmeaError> {
+            match self.rest {
+                Some(_) => self.next_number(),
+                None => Ok(None),
+            }
+        }
+
+        /// The first character of the next field, eg a status or mode letter.
+        pub fn next_char(&mut self) -> Result<Option<char>, NmeaError> {
+            Ok(self.next_field()?.chars().next())
+        }
+
+        /// Latitude ddmm.mmmm and N or S, in degrees. South is negative. BadNumber past 90.
+        pub fn next_lat(&mut self) -> Result<Option<f64>, NmeaError> {
+            self.next_degrees('N', 'S', 90.0)
+        }
+
+        /// Longitude dddmm.mmmm and E or W, in degrees. West is negative. BadNumber past 180.
+        pub fn next_long(&mut self) -> Result<Option<f64>, NmeaError> {
+            self.next_degrees('E', 'W', 180.0)
         }
-        let deg: f32;
-        let minutes: f32;
-        let first_half: Vec<&str> = degrees.split('.').collect();
 
-        if first_half[0].len() == 4 {
-            deg = degrees[0..2].parse::<f32>().unwrap();
-            minutes = (degrees[2..].parse::<f32>().unwrap()) / 60.0;
-        } else {
-            deg = degrees[0..3].parse::<f32>().unwrap();
-            minutes = (degrees[3..].parse::<f32>().unwrap()) / 60.0;
+        fn next_degrees(&mut self, positive: char, negative: char, max: f64) -> Result<Option<f64>, NmeaError> {
+            let index = self.index;
+            let degrees = self.next_field()?;
+            let compass_direction = self.next_char()?;
+            if degrees.is_empty() {
+                return Ok(None);
+            }
+            let degrees = _parse_degrees(degrees)
+                .filter(|degrees| *degrees <= max)
+                .ok_or(NmeaError::BadNumber(index))?;
+            match compass_direction {
+                Some(direction) if direction == positive => Ok(Some(degrees)),
+                Some(direction) if direction == negative => Ok(Some(-degrees)),
+                _ => Err(NmeaError::BadDirection(index + 1)),
+            }
         }
+    }
 
-        let r: f32 = deg + minutes;
-        let r: f32 = format!("{:.6}", r).parse().unwrap(); // Round to 6 decimal places.
+    impl<'a> Iterator for Fields<'a> {
+        type Item = &'a str;
 
-        if (compass_direction == "N") | (compass_direction == "E") {
-            return Some(r);
-        } else if (compass_direction == "S") | (compass_direction == "W") {
-            return Some(r * -1.0);
-        } else {
-            panic!("Compass direction not north or south")
+        fn next(&mut self) -> Option<&'a str> {
+            self.next_field().ok()
         }
     }
 
+    /// Parses NMEA lat/long dddmm.mmmm into degrees, without the compass direction. None if it
+    /// isn't a number, or the minutes aren't in 0 to 60.
+    ///
+    /// All the decimal places the gps gives are kept: an f64 holds far more than the 1e-7 degrees
+    /// (about a centimetre) an MTK chip gives.
+    pub fn _parse_degrees(degrees: &str) -> Option<f64> {
+        // ddd is degrees, mm.mmmm is minutes
+        // NMEA format is either ddmm.mmmmm or dddmm.mmmmm
+        let first_half: &str = degrees.split('.').next().unwrap_or("");
+        let split = if first_half.len() == 4 { 2 } else { 3 };
+        let deg: f64 = degrees.get(0..split)?.parse().ok()?;
+        let minutes: f64 = degrees.get(split..)?.parse().ok()?;
+        if !(0.0..60.0).contains(&minutes) {
+            return None;
+        }
+        Some(deg + minutes / 60.0)
+    }
+
+    #[cfg(feature = "std")]
     pub fn _format_hhmmss(time: &str) -> String {
         // Take in a string of hhmmss and return it as a formatted hh-mm-ss
         if time.len() < 6 {
@@ -79,20 +263,465 @@ pub mod parse_nmea {
         return format!("{}:{}:{}", hours, mins, secs);
     }
 
-    pub fn parse_sentence(sentence: &str) -> Option<Vec<&str>> {
-        // Assumes that a valid sentence is always given.
-        // Convert sentence into a split vec along ','.
-
+    /// The fields of a sentence if its checksum is valid.
+    pub fn parse_sentence(sentence: &str) -> Option<Fields<'_>> {
         let sentence = sentence.trim(); // Remove whitespace.
-        if sentence.len() < 6 {
+        if sentence.len() < 6 || !open_gps::gps::is_valid_checksum(sentence) {
             return None;
         }
-        return if open_gps::gps::is_valid_checksum(sentence) {
-            let sentence: &str = &sentence[0..sentence.len() - 3]; // Remove checksum.
-            Some(sentence.split(",").collect())
-        } else {
-            None
-        };
+        Some(Fields::new(&sentence[0..sentence.len() - 3])) // Remove checksum.
+    }
+}
+
+pub mod write_nmea {
+    //! Writing data back into NMEA sentences, the other way round from parse_nmea.
+    //!
+    //! Each data struct has to_sentence(talker), eg GgaData::to_sentence(Talker::Gps), giving the
+    //! sentence with its $, checksum and \r\n ready to send to a chart plotter or write to a file.
+    //! A GSV set can take more than one sentence, so GsvData has to_sentences.
+    //!
+    //! Numbers are written with as few decimal places as give the same number back when parsed.
+    //! Latitude and longitude are written to 6 decimal places of a minute, about 2mm.
+
+    use core::fmt::{self, Write};
+
+    use heapless::String;
+
+    use crate::open_gps::gps::MAX_SENTENCE_LENGTH;
+    use crate::pmtk::send_pmtk::add_checksum;
+
+    use super::datetime::{NmeaDate, NmeaTime};
+    use super::talker::Talker;
+
+    /// Builds a sentence one field at a time, the other way round from Fields.
+    /// ```ignore
+    /// let mut sentence = SentenceWriter::new(Talker::Gps, "VTG");
+    /// sentence.push_number(Some(89.68));
+    /// sentence.push_field("T");
+    /// let sentence: String<MAX_SENTENCE_LENGTH> = sentence.finish(); // $GPVTG,89.68,T*..\r\n
+    /// ```
+    #[derive(Debug, Clone)]
+    pub struct SentenceWriter {
+        body: String<MAX_SENTENCE_LENGTH>,
+    }
+
+    impl SentenceWriter {
+        /// Starts the sentence with its header, eg $GPGGA for Talker::Gps and "GGA".
+        pub fn new(talker: Talker, sentence_type: &str) -> SentenceWriter {
+            let mut body = String::new();
+            let _ = body.push_str(talker.prefix());
+            let _ = body.push_str(sentence_type);
+            SentenceWriter { body }
+        }
+
+        /// Adds a field as it is. Past MAX_SENTENCE_LENGTH the sentence is cut short.
+        pub fn push_field<T: fmt::Display>(&mut self, field: T) {
+            let _ = write!(self.body, ",{}", field);
+        }
+
+        /// Adds a number, an empty field for None.
+        pub fn push_number<T: fmt::Display>(&mut self, number: Option<T>) {
+            match number {
+                Some(number) => self.push_field(number),
+                None => self.push_field(""),
+            }
+        }
+
+        /// Adds latitude in degrees as ddmm.mmmmmm and N or S.
+        pub fn push_lat(&mut self, latitude: Option<f64>) {
+            self.push_degrees(latitude, 2, 'N', 'S');
+        }
+
+        /// Adds longitude in degrees as dddmm.mmmmmm and E or W.
+        pub fn push_long(&mut self, longitude: Option<f64>) {
+            self.push_degrees(longitude, 3, 'E', 'W');
+        }
+
+        fn push_degrees(&mut self, degrees: Option<f64>, width: usize, positive: char, negative: char) {
+            let degrees = match degrees {
+                Some(degrees) => degrees,
+                None => return self.push_field(","),
+            };
+            // In millionths of a minute, so rounding up to 60 minutes carries into the degrees.
+            // Rounded by adding a half, round isn't in core.
+            let micro_minutes = (degrees.abs() * 60_000_000.0 + 0.5) as u64;
+            let minutes = micro_minutes / 1_000_000;
+            let direction = if degrees < 0.0 { negative } else { positive };
+            let _ = write!(self.body, ",{:0width$}{:02}.{:06},{}",
+                           minutes / 60, minutes % 60, micro_minutes % 1_000_000, direction, width = width);
+        }
+
+        /// Adds a time as hhmmss.sss.
+        pub fn push_time(&mut self, time: Option<NmeaTime>) {
+            match time {
+                Some(time) => {
+                    let _ = write!(self.body, ",{:02}{:02}{:02}.{:03}", time.hour, time.minute, time.second, time.millis);
+                }
+                None => self.push_field(""),
+            }
+        }
+
+        /// Adds a date as RMC's ddmmyy.
+        pub fn push_date(&mut self, date: Option<NmeaDate>) {
+            match date {
+                Some(date) => {
+                    let _ = write!(self.body, ",{:02}{:02}{:02}", date.day, date.month, date.year % 100);
+                }
+                None => self.push_field(""),
+            }
+        }
+
+        /// The sentence with its $, checksum and \r\n.
+        pub fn finish(&self) -> String<MAX_SENTENCE_LENGTH> {
+            add_checksum(&self.body)
+        }
+    }
+}
+
+pub mod datetime {
+    //! # UTC time and date
+    //!
+    //! NmeaTime is the hhmmss.sss of a sentence and NmeaDate its ddmmyy (or ZDA's day, month,
+    //! year). RMC and ZDA give both, together an NmeaDateTime.
+    //!
+    //! Use seconds_since for how long it's been between two times, taking the hhmmss values away
+    //! from each other is wrong across a minute or hour.
+    //!
+    //! With the chrono or time feature they convert to chrono and time types.
+    use core::fmt;
+    use core::str::FromStr;
+
+    use serde::{Serialize, Deserialize};
+
+    const MILLIS_PER_DAY: i64 = 86_400_000;
+
+    /// UTC time of day. second can be 60 for a leap second.
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default, Serialize, Deserialize, Clone, Copy)]
+    pub struct NmeaTime {
+        pub hour: u8,
+        pub minute: u8,
+        pub second: u8,
+        pub millis: u16,
+    }
+
+    impl NmeaTime {
+        /// None if it isn't a time of day.
+        pub fn new(hour: u8, minute: u8, second: u8, millis: u16) -> Option<NmeaTime> {
+            if hour < 24 && minute < 60 && second <= 60 && millis < 1000 {
+                Some(NmeaTime { hour, minute, second, millis })
+            } else {
+                None
+            }
+        }
+
+        /// Milliseconds since midnight.
+        pub fn millis_of_day(&self) -> u32 {
+            ((self.hour as u32 * 60 + self.minute as u32) * 60 + self.second as u32) * 1000 + self.millis as u32
+        }
+
+        /// Seconds since midnight.
+        pub fn seconds_of_day(&self) -> f64 {
+            self.millis_of_day() as f64 / 1000.0
+        }
+
+        /// Seconds from an earlier time to this one. If this one is earlier in the day it's taken
+        /// as being after midnight.
+        pub fn seconds_since(&self, earlier: NmeaTime) -> f64 {
+            let millis = (self.millis_of_day() as i64 - earlier.millis_of_day() as i64).rem_euclid(MILLIS_PER_DAY);
+            millis as f64 / 1000.0
+        }
+
+        #[cfg(feature = "chrono")]
+        pub fn to_chrono(&self) -> Option<chrono::NaiveTime> {
+            // chrono gives a leap second as 59 seconds and over 1000 millis.
+            let (second, millis) = if self.second == 60 { (59, self.millis as u32 + 1000) } else { (self.second, self.millis as u32) };
+            chrono::NaiveTime::from_hms_milli_opt(self.hour as u32, self.minute as u32, second as u32, millis)
+        }
+
+        /// None for a leap second, time can't hold one.
+        #[cfg(feature = "time")]
+        pub fn to_time(&self) -> Option<::time::Time> {
+            ::time::Time::from_hms_milli(self.hour, self.minute, self.second, self.millis).ok()
+        }
+    }
+
+    /// From hhmmss with any number of decimal places, past 3 are cut off. Eg "131613.000".
+    impl FromStr for NmeaTime {
+        type Err = ();
+
+        fn from_str(hhmmss: &str) -> Result<NmeaTime, ()> {
+            let (whole, fraction) = hhmmss.split_once('.').unwrap_or((hhmmss, ""));
+            let digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+            if whole.len() != 6 || !digits(whole) || !digits(fraction) {
+                return Err(());
+            }
+            let number = |digits: &str| digits.parse::<u8>().map_err(|_e| ());
+            let mut millis: u16 = 0;
+            for (place, digit) in fraction.bytes().take(3).enumerate() {
+                millis += (digit - b'0') as u16 * [100, 10, 1][place];
+            }
+            NmeaTime::new(number(&whole[0..2])?, number(&whole[2..4])?, number(&whole[4..6])?, millis).ok_or(())
+        }
+    }
+
+    /// hh:mm:ss.sss
+    impl fmt::Display for NmeaTime {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:02}:{:02}:{:02}.{:03}", self.hour, self.minute, self.second, self.millis)
+        }
+    }
+
+    /// UTC date. year has all 4 digits.
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default, Serialize, Deserialize, Clone, Copy)]
+    pub struct NmeaDate {
+        pub year: u16,
+        pub month: u8,
+        pub day: u8,
+    }
+
+    impl NmeaDate {
+        /// None if the day isn't in the month.
+        pub fn new(year: u16, month: u8, day: u8) -> Option<NmeaDate> {
+            let leap_year = match (year % 4, year % 100, year % 400) {
+                (_, _, 0) => true,
+                (_, 0, _) => false,
+                (0, _, _) => true,
+                _ => false,
+            };
+            let days_in_month = match month {
+                1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+                4 | 6 | 9 | 11 => 30,
+                2 if leap_year => 29,
+                2 => 28,
+                _ => return None,
+            };
+            if (1..=days_in_month).contains(&day) {
+                Some(NmeaDate { year, month, day })
+            } else {
+                None
+            }
+        }
+
+        /// Days since 1970-01-01.
+        pub fn days_since_epoch(&self) -> i64 {
+            // Days from civil, http://howardhinnant.github.io/date_algorithms.html
+            let year = self.year as i64 - if self.month <= 2 { 1 } else { 0 };
+            let era = year.div_euclid(400);
+            let year_of_era = year - era * 400;
+            let month = self.month as i64;
+            let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + self.day as i64 - 1;
+            let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+            era * 146_097 + day_of_era - 719_468
+        }
+
+        #[cfg(feature = "chrono")]
+        pub fn to_chrono(&self) -> Option<chrono::NaiveDate> {
+            chrono::NaiveDate::from_ymd_opt(self.year as i32, self.month as u32, self.day as u32)
+        }
+
+        #[cfg(feature = "time")]
+        pub fn to_time(&self) -> Option<::time::Date> {
+            use core::convert::TryFrom;
+            let month = ::time::Month::try_from(self.month).ok()?;
+            ::time::Date::from_calendar_date(self.year as i32, month, self.day).ok()
+        }
+    }
+
+    /// From RMC's ddmmyy. yy is taken as 2000 to 2079, or 1980 to 1999 from 80 up as GPS started
+    /// in 1980.
+    impl FromStr for NmeaDate {
+        type Err = ();
+
+        fn from_str(ddmmyy: &str) -> Result<NmeaDate, ()> {
+            if ddmmyy.len() != 6 || !ddmmyy.bytes().all(|byte| byte.is_ascii_digit()) {
+                return Err(());
+            }
+            let number = |digits: &str| digits.parse::<u8>().map_err(|_e| ());
+            let yy = number(&ddmmyy[4..6])? as u16;
+            let year = if yy < 80 { 2000 + yy } else { 1900 + yy };
+            NmeaDate::new(year, number(&ddmmyy[2..4])?, number(&ddmmyy[0..2])?).ok_or(())
+        }
+    }
+
+    /// yyyy-mm-dd
+    impl fmt::Display for NmeaDate {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+        }
+    }
+
+    /// A full UTC timestamp, from RMC or ZDA.
+    #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default, Serialize, Deserialize, Clone, Copy)]
+    pub struct NmeaDateTime {
+        pub date: NmeaDate,
+        pub time: NmeaTime,
+    }
+
+    impl NmeaDateTime {
+        /// Milliseconds since 1970-01-01 00:00:00 UTC, leap seconds not counted.
+        pub fn unix_millis(&self) -> i64 {
+            self.date.days_since_epoch() * MILLIS_PER_DAY + self.time.millis_of_day() as i64
+        }
+
+        /// Seconds from an earlier timestamp to this one, negative if it's later.
+        pub fn seconds_since(&self, earlier: NmeaDateTime) -> f64 {
+            (self.unix_millis() - earlier.unix_millis()) as f64 / 1000.0
+        }
+
+        #[cfg(feature = "chrono")]
+        pub fn to_chrono(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+            use chrono::TimeZone;
+            let naive = self.date.to_chrono()?.and_time(self.time.to_chrono()?);
+            Some(chrono::Utc.from_utc_datetime(&naive))
+        }
+
+        #[cfg(feature = "time")]
+        pub fn to_time(&self) -> Option<::time::OffsetDateTime> {
+            Some(::time::PrimitiveDateTime::new(self.date.to_time()?, self.time.to_time()?).assume_utc())
+        }
+    }
+
+    /// yyyy-mm-ddThh:mm:ss.sssZ
+    impl fmt::Display for NmeaDateTime {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{}T{}Z", self.date, self.time)
+        }
+    }
+}
+
+pub mod talker {
+    //! # Talker
+    //! The first two letters of the header say which constellation the sentence is from.
+    //!
+    //! In the multi-constellation modes the fix sentences (GGA, RMC, VTG, GLL) come from GN, and
+    //! there is a GSA and set of GSVs for each constellation.
+
+    use serde::{Deserialize, Serialize};
+
+    /// Who gave the sentence.
+    /// - Gps -> GP
+    /// - Glonass -> GL
+    /// - Galileo -> GA
+    /// - Beidou -> GB or BD
+    /// - Gnss -> GN, a fix from more than one constellation.
+    /// - Qzss -> QZ or GQ
+    /// - Unknown -> Any other talker.
+    #[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
+    pub enum Talker {
+        Gps,
+        Glonass,
+        Galileo,
+        Beidou,
+        Gnss,
+        Qzss,
+        Unknown,
+    }
+
+    impl Talker {
+        /// The talker of a header, eg $GLGSV.
+        pub fn from_header(header: &str) -> Talker {
+            match header.get(1..3).unwrap_or("") {
+                "GP" => Talker::Gps,
+                "GL" => Talker::Glonass,
+                "GA" => Talker::Galileo,
+                "GB" | "BD" => Talker::Beidou,
+                "GN" => Talker::Gnss,
+                "QZ" | "GQ" => Talker::Qzss,
+                _ => Talker::Unknown,
+            }
+        }
+
+        /// The talker of an NMEA 4.10 GNSS system ID: 1 GPS, 2 GLONASS, 3 Galileo, 4 BeiDou, 5 QZSS.
+        pub fn from_system_id(system_id: i32) -> Talker {
+            match system_id {
+                1 => Talker::Gps,
+                2 => Talker::Glonass,
+                3 => Talker::Galileo,
+                4 => Talker::Beidou,
+                5 => Talker::Qzss,
+                _ => Talker::Unknown,
+            }
+        }
+
+        /// The two letters a sentence from the talker starts with. Unknown gives GN.
+        pub fn prefix(&self) -> &'static str {
+            match self {
+                Talker::Gps => "GP",
+                Talker::Glonass => "GL",
+                Talker::Galileo => "GA",
+                Talker::Beidou => "GB",
+                Talker::Gnss | Talker::Unknown => "GN",
+                Talker::Qzss => "QZ",
+            }
+        }
+
+        /// The NMEA 4.10 GNSS system ID of the talker, None for Gnss and Unknown.
+        pub fn system_id(&self) -> Option<i32> {
+            match self {
+                Talker::Gps => Some(1),
+                Talker::Glonass => Some(2),
+                Talker::Galileo => Some(3),
+                Talker::Beidou => Some(4),
+                Talker::Qzss => Some(5),
+                Talker::Gnss | Talker::Unknown => None,
+            }
+        }
+    }
+}
+
+pub mod satellite {
+    //! # Satellite ids
+    //!
+    //! GSA and GSV give satellites by PRN number. Before NMEA 4.10 one set of numbers covers every
+    //! constellation: 1-32 GPS, 33-64 SBAS, 65-96 GLONASS, 193-202 QZSS. From NMEA 4.10 Galileo
+    //! and BeiDou (and sometimes the others) number their satellites from 1 under their own
+    //! talker, so the talker is needed to know the constellation.
+
+    use serde::{Serialize, Deserialize};
+
+    use super::talker::Talker;
+
+    /// The constellation a satellite is from.
+    /// - Sbas -> Satellite-based augmentation, eg WAAS or EGNOS.
+    /// - Unknown -> A PRN outside all the ranges.
+    #[derive(Debug, PartialEq, Eq, Hash, Serialize, Deserialize, Clone, Copy, Default)]
+    pub enum Constellation {
+        Gps,
+        Sbas,
+        Glonass,
+        Galileo,
+        Beidou,
+        Qzss,
+        #[default]
+        Unknown,
+    }
+
+    /// A satellite, the same in GSA's satellites used and GSV's satellites in view.
+    /// - system: [Constellation](nmea/satellite/enum.Constellation.html)
+    /// - prn: The PRN number as the gps gives it.
+    #[derive(Debug, PartialEq, Eq, Hash, Serialize, Deserialize, Clone, Copy, Default)]
+    pub struct SatelliteId {
+        pub system: Constellation,
+        pub prn: u16,
+    }
+
+    impl SatelliteId {
+        /// The satellite of a PRN given by the talker. The Glonass, Galileo, BeiDou and QZSS
+        /// talkers only give their own satellites, for any other talker it's from the PRN.
+        pub fn from_prn(talker: Talker, prn: u16) -> SatelliteId {
+            let system = match (talker, prn) {
+                (Talker::Glonass, _) => Constellation::Glonass,
+                (Talker::Galileo, _) => Constellation::Galileo,
+                (Talker::Beidou, _) => Constellation::Beidou,
+                (Talker::Qzss, _) => Constellation::Qzss,
+                (_, 1..=32) => Constellation::Gps,
+                (_, 33..=64) => Constellation::Sbas,
+                (_, 65..=96) => Constellation::Glonass,
+                (_, 193..=202) => Constellation::Qzss,
+                _ => Constellation::Unknown,
+            };
+            SatelliteId { system, prn }
+        }
     }
 }
 
@@ -102,23 +731,112 @@ pub mod gga {
     //!
     //!
 
-    use super::parse_nmea::*;
+    use heapless::String;
     use serde::{Serialize, Deserialize};
 
-    /// Satellite fix type
-    /// - NoFix -> No satellites being received. Default.
-    /// - GpsFix -> Just has a fix using satellites.
-    /// - DgpsFix -> Differential GPS. Uses readings from ground stations to reduce error.
+    use crate::open_gps::gps::MAX_SENTENCE_LENGTH;
+
+    use super::datetime::NmeaTime;
+    use super::gns::Mode;
+    use super::parse_nmea::*;
+    use super::talker::Talker;
+    use super::write_nmea::SentenceWriter;
+
+    /// Satellite fix type, the GGA fix quality.
+    /// - NoFix -> 0, No satellites being received. Default, and any quality past 8.
+    /// - GpsFix -> 1, Just has a fix using satellites.
+    /// - DgpsFix -> 2, Differential GPS. Uses readings from ground stations to reduce error.
+    /// - PpsFix -> 3, Precise Positioning Service, the military signal.
+    /// - RtkFixed -> 4, Real time kinematic with fixed integers.
+    /// - RtkFloat -> 5, Real time kinematic with floating integers.
+    /// - Estimated -> 6, Dead reckoning, eg in a tunnel.
+    /// - Manual -> 7, Position put in by hand.
+    /// - Simulation -> 8, From a simulator.
+    ///
+    /// Each is the same fix as one of the mode letters RMC, GLL, VTG and GNS give, see mode.
     #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Default)]
     pub enum SatFix {
         #[default]
         NoFix,
         GpsFix,
         DgpsFix,
+        PpsFix,
+        RtkFixed,
+        RtkFloat,
+        Estimated,
+        Manual,
+        Simulation,
+    }
+
+    impl SatFix {
+        /// The fix of a GGA fix quality field, NoFix if it isn't 1 to 8.
+        pub fn from_quality(quality: &str) -> SatFix {
+            match quality {
+                "1" => SatFix::GpsFix,
+                "2" => SatFix::DgpsFix,
+                "3" => SatFix::PpsFix,
+                "4" => SatFix::RtkFixed,
+                "5" => SatFix::RtkFloat,
+                "6" => SatFix::Estimated,
+                "7" => SatFix::Manual,
+                "8" => SatFix::Simulation,
+                _ => SatFix::NoFix,
+            }
+        }
+
+        /// The GGA fix quality number, 0 to 8.
+        pub fn quality(&self) -> u8 {
+            match self {
+                SatFix::NoFix => 0,
+                SatFix::GpsFix => 1,
+                SatFix::DgpsFix => 2,
+                SatFix::PpsFix => 3,
+                SatFix::RtkFixed => 4,
+                SatFix::RtkFloat => 5,
+                SatFix::Estimated => 6,
+                SatFix::Manual => 7,
+                SatFix::Simulation => 8,
+            }
+        }
+
+        /// The mode letter for the same fix, as RMC, GLL, VTG and GNS give it.
+        pub fn mode(&self) -> Mode {
+            match self {
+                SatFix::NoFix => Mode::NoFix,
+                SatFix::GpsFix => Mode::Autonomous,
+                SatFix::DgpsFix => Mode::Differential,
+                SatFix::PpsFix => Mode::Precise,
+                SatFix::RtkFixed => Mode::RtkFixed,
+                SatFix::RtkFloat => Mode::RtkFloat,
+                SatFix::Estimated => Mode::Estimated,
+                SatFix::Manual => Mode::Manual,
+                SatFix::Simulation => Mode::Simulator,
+            }
+        }
+
+        /// The fix of a mode letter, NoFix for Unknown.
+        pub fn from_mode(mode: Mode) -> SatFix {
+            match mode {
+                Mode::NoFix | Mode::Unknown => SatFix::NoFix,
+                Mode::Autonomous => SatFix::GpsFix,
+                Mode::Differential => SatFix::DgpsFix,
+                Mode::Precise => SatFix::PpsFix,
+                Mode::RtkFixed => SatFix::RtkFixed,
+                Mode::RtkFloat => SatFix::RtkFloat,
+                Mode::Estimated => SatFix::Estimated,
+                Mode::Manual => SatFix::Manual,
+                Mode::Simulator => SatFix::Simulation,
+            }
+        }
+
+        /// True for everything but NoFix, an estimated fix is still a position.
+        pub fn has_fix(&self) -> bool {
+            *self != SatFix::NoFix
+        }
     }
 
     /// GGA data struct.
-    /// - utc -> UTC
+    /// - utc -> UTC [NmeaTime](nmea/datetime/struct.NmeaTime.html)
     /// - lat -> Latitude
     /// - long -> Longitude
     /// - sat_fix -> Satellite fix type -> [SatFix](nmea/gga/enum.SatFix.html)
@@ -129,9 +847,9 @@ pub mod gga {
     /// - age_diff_corr -> Age in seconds since last update from reference station.
     #[derive(Debug, PartialEq, Default, Serialize, Deserialize, Clone)]
     pub struct GgaData {
-        pub utc: f64,
-        pub lat: Option<f32>,
-        pub long: Option<f32>,
+        pub utc: NmeaTime,
+        pub lat: Option<f64>,
+        pub long: Option<f64>,
         pub sat_fix: SatFix,
         pub satellites_used: i32,
         pub hdop: Option<f32>,
@@ -140,39 +858,50 @@ pub mod gga {
         pub age_diff_corr: Option<f32>,
     }
 
-    /// Take a parse_sentence vec<&str> and output GgaData.
-    pub fn parse_gga(args: Vec<&str>) -> GgaData {
-        //! ${GP,GL,GA,GN}GGA, UTC, lat, N/S, long, E/S, Fix quality, Sats used, HDOP, Alt, Alt Units,
-        //! Geoidal separation, Geo units, Age of diff corr, * checksum
-        //!
-        //! Time, sat fix and sats used always given.
-        let header = args.get(0).unwrap();
-        if &header[3..5] != "GG" {
-            panic!(
-                "Sentence is not a GGA format, it's {} format",
-                header
-            )
+    impl GgaData {
+        /// The GGA sentence from the talker, the station id is left empty.
+        pub fn to_sentence(&self, talker: Talker) -> String<MAX_SENTENCE_LENGTH> {
+            let mut sentence = SentenceWriter::new(talker, "GGA");
+            sentence.push_time(Some(self.utc));
+            sentence.push_lat(self.lat);
+            sentence.push_long(self.long);
+            sentence.push_field(self.sat_fix.quality());
+            sentence.push_field(self.satellites_used);
+            sentence.push_number(self.hdop);
+            sentence.push_number(self.msl_alt);
+            sentence.push_field("M");
+            sentence.push_number(self.geoidal_sep);
+            sentence.push_field("M");
+            sentence.push_number(self.age_diff_corr);
+            sentence.push_field("");
+            sentence.finish()
         }
+    }
+
+    /// Take the parse_sentence fields and output GgaData.
+    pub fn parse_gga(mut fields: Fields) -> Result<GgaData, NmeaError> {
+        // ${GP,GL,GA,GN}GGA, UTC, lat, N/S, long, E/S, Fix quality, Sats used, HDOP, Alt, Alt Units,
+        // Geoidal separation, Geo units, Age of diff corr, * checksum
+        //
+        // Time, sat fix and sats used always given.
+        fields.check_header("GGA")?;
 
         // Parse time
-        let utc: f64 = args.get(1).unwrap().parse().unwrap();
+        let utc: NmeaTime = fields.next_required()?;
 
         // Parse lat
-        let lat: Option<f32> = _parse_degrees(args.get(2).unwrap(), args.get(3).unwrap());
-        let long: Option<f32> = _parse_degrees(args.get(4).unwrap(), args.get(5).unwrap());
-
-        let sat_fix = match *args.get(6).unwrap() {
-            "0" => SatFix::NoFix,
-            "1" => SatFix::GpsFix,
-            "2" => SatFix::DgpsFix,
-            _ => SatFix::NoFix,
-        };
-        let satellites_used: i32 = args.get(7).unwrap().parse().unwrap();
-        let hdop = args.get(8).unwrap().parse::<f32>().ok();
-        let msl_alt: Option<f32> = args.get(9).unwrap().parse::<f32>().ok();
-        let geoidal_sep: Option<f32> = args.get(11).unwrap().parse::<f32>().ok();
-        let age_diff_corr: Option<f32> = args.get(13).unwrap().parse::<f32>().ok();
-        return GgaData {
+        let lat: Option<f64> = fields.next_lat()?;
+        let long: Option<f64> = fields.next_long()?;
+
+        let sat_fix = SatFix::from_quality(fields.next_field()?);
+        let satellites_used: i32 = fields.next_required()?;
+        let hdop: Option<f32> = fields.next_f32()?;
+        let msl_alt: Option<f32> = fields.next_f32()?;
+        fields.skip_fields(1)?; // Altitude units, M.
+        let geoidal_sep: Option<f32> = fields.next_f32()?;
+        fields.skip_fields(1)?;
+        let age_diff_corr: Option<f32> = fields.next_f32()?;
+        Ok(GgaData {
             utc,
             lat,
             long,
@@ -182,7 +911,7 @@ pub mod gga {
             msl_alt,
             geoidal_sep,
             age_diff_corr,
-        };
+        })
     }
 }
 
@@ -191,8 +920,19 @@ pub mod gsa {
     //!
     //! Gives All the satellites that are being tracked and the HDOP, VDOP, PDOP.
 
+    use heapless::{String, Vec};
     use serde::{Serialize, Deserialize};
 
+    use crate::open_gps::gps::MAX_SENTENCE_LENGTH;
+
+    use super::parse_nmea::*;
+    use super::satellite::SatelliteId;
+    use super::talker::Talker;
+    use super::write_nmea::SentenceWriter;
+
+    /// Most satellites a GSA can give as used.
+    pub const MAX_SATELLITES_USED: usize = 12;
+
     /// Manual or automatic selection mode for 3d or 2d fix.
     #[derive(PartialEq, Debug, Serialize, Deserialize, Clone, Default)]
     pub enum Mode {
@@ -215,37 +955,54 @@ pub mod gsa {
     /// # GSA data struct
     /// - mode -> [Mode](nmea/gsa/enum.Mode.html)
     /// - dimension_fix -> [DimensionFix](nmea/gsa/enum.DimensionFix.html)
-    /// - sat1 -> Satellite 1 id number
-    /// - sat2 -> Satellite 2 id number
-    /// - ... -> up to 12 satellites.
+    /// - satellites -> The [SatelliteId](nmea/satellite/struct.SatelliteId.html) of each satellite
+    ///   used, up to 12.
     /// - pdop -> Positional Dilution of Precisions
     /// - hdop -> Horizontal Dilution of Precisions
     /// - vdop -> Vertical Dilution of Precisions
+    /// - system -> The constellation the satellites are from, from the NMEA 4.10 GNSS system ID.
+    ///   None before NMEA 4.10.
     #[derive(PartialEq, Debug, Default, Serialize, Deserialize, Clone)]
     pub struct GsaData {
         pub mode: Mode,
         pub dimension_fix: DimensionFix,
-        pub sat1: Option<i32>,
-        pub sat2: Option<i32>,
-        pub sat3: Option<i32>,
-        pub sat4: Option<i32>,
-        pub sat5: Option<i32>,
-        pub sat6: Option<i32>,
-        pub sat7: Option<i32>,
-        pub sat8: Option<i32>,
-        pub sat9: Option<i32>,
-        pub sat10: Option<i32>,
-        pub sat11: Option<i32>,
-        pub sat12: Option<i32>,
+        pub satellites: Vec<SatelliteId, MAX_SATELLITES_USED>,
         pub pdop: Option<f32>,
         pub hdop: Option<f32>,
         pub vdop: Option<f32>,
+        pub system: Option<Talker>,
     }
 
-    pub fn parse_gsa(args: Vec<&str>) -> GsaData {
+    impl GsaData {
+        /// The GSA sentence from the talker. The GNSS system ID is only added when system is given.
+        pub fn to_sentence(&self, talker: Talker) -> String<MAX_SENTENCE_LENGTH> {
+            let mut sentence = SentenceWriter::new(talker, "GSA");
+            sentence.push_field(match self.mode {
+                Mode::Manual => "M",
+                Mode::Automatic => "A",
+            });
+            sentence.push_field(match self.dimension_fix {
+                DimensionFix::NotAvailable => 1,
+                DimensionFix::Dimension2d => 2,
+                DimensionFix::Dimension3d => 3,
+            });
+            for satellite in 0..MAX_SATELLITES_USED {
+                sentence.push_number(self.satellites.get(satellite).map(|satellite| satellite.prn));
+            }
+            sentence.push_number(self.pdop);
+            sentence.push_number(self.hdop);
+            sentence.push_number(self.vdop);
+            if let Some(system) = self.system {
+                sentence.push_number(system.system_id());
+            }
+            sentence.finish()
+        }
+    }
+
+    pub fn parse_gsa(mut fields: Fields) -> Result<GsaData, NmeaError> {
         //! Format
         //! $G{}GSA, Mode, dimention_fix, Sat1, Sat2, Sat3, Sat4, Sat5, Sat6, Sat7, Sat8, Sat9, Sat10,
-        //! Sat11, Sat12, PDOP, HDOP, VDOP  *checksum
+        //! Sat11, Sat12, PDOP, HDOP, VDOP, GNSS system ID (NMEA 4.10) *checksum
         //!
         //! Mode1 (Mode)
         //! - M (Manual - forced to operate in 2D or 3D mode),
@@ -257,62 +1014,48 @@ pub mod gsa {
         //! - 3- 3D (>= 4 SVs used)
         //!
         //! Mode and DimentionFix should always be given. The other values don't have to be.
+        //!
+        //! The satellites' constellation is from the GNSS system ID when it's given, otherwise the
+        //! talker and PRN.
 
-        let header = args.get(0).unwrap();
-        if &header[3..6] != "GSA" {
-            panic!(
-                "Incorrect sentence header. Should be GSA, it is {}",
-                header
-            )
-        }
+        let talker = fields.talker();
+        fields.check_header("GSA")?;
 
-        let mode = match *args.get(1).unwrap() {
-            "M" => Mode::Manual,
-            "A" => Mode::Automatic,
+        let mode = match fields.next_char()? {
+            Some('M') => Mode::Manual,
+            Some('A') => Mode::Automatic,
             _ => Mode::Manual, // Default.
         };
-        let dimension_fix = match *args.get(2).unwrap() {
+        let dimension_fix = match fields.next_field()? {
             "1" => DimensionFix::NotAvailable,
             "2" => DimensionFix::Dimension2d,
             "3" => DimensionFix::Dimension3d,
             _ => DimensionFix::NotAvailable,
         };
-        let sat1: Option<i32> = args.get(3).unwrap().parse::<i32>().ok();
-        let sat2: Option<i32> = args.get(4).unwrap().parse::<i32>().ok();
-        let sat3: Option<i32> = args.get(5).unwrap().parse::<i32>().ok();
-        let sat4: Option<i32> = args.get(6).unwrap().parse::<i32>().ok();
-        let sat5: Option<i32> = args.get(7).unwrap().parse::<i32>().ok();
-        let sat6: Option<i32> = args.get(8).unwrap().parse::<i32>().ok();
-        let sat7: Option<i32> = args.get(9).unwrap().parse::<i32>().ok();
-        let sat8: Option<i32> = args.get(10).unwrap().parse::<i32>().ok();
-        let sat9: Option<i32> = args.get(11).unwrap().parse::<i32>().ok();
-        let sat10: Option<i32> = args.get(12).unwrap().parse::<i32>().ok();
-        let sat11: Option<i32> = args.get(13).unwrap().parse::<i32>().ok();
-        let sat12: Option<i32> = args.get(14).unwrap().parse::<i32>().ok();
-
-        let pdop: Option<f32> = args.get(15).unwrap().parse::<f32>().ok();
-        let hdop: Option<f32> = args.get(16).unwrap().parse::<f32>().ok();
-        let vdop: Option<f32> = args.get(17).unwrap().parse::<f32>().ok();
-
-        return GsaData {
+        let mut prns: Vec<u16, MAX_SATELLITES_USED> = Vec::new();
+        for _satellite in 0..MAX_SATELLITES_USED {
+            if let Some(prn) = fields.next_number()? {
+                let _ = prns.push(prn);
+            }
+        }
+
+        let pdop: Option<f32> = fields.next_f32()?;
+        let hdop: Option<f32> = fields.next_f32()?;
+        let vdop: Option<f32> = fields.next_f32()?;
+        let system: Option<Talker> = fields.next_trailing()?.map(Talker::from_system_id);
+        let satellites = prns.into_iter()
+            .map(|prn| SatelliteId::from_prn(system.unwrap_or(talker), prn))
+            .collect();
+
+        Ok(GsaData {
             mode,
             dimension_fix,
-            sat1,
-            sat2,
-            sat3,
-            sat4,
-            sat5,
-            sat6,
-            sat7,
-            sat8,
-            sat9,
-            sat10,
-            sat11,
-            sat12,
+            satellites,
             pdop,
             hdop,
             vdop,
-        };
+            system,
+        })
     }
 }
 
@@ -322,27 +1065,119 @@ pub mod gsv {
     //! GSV gives satellites in view. If there are many satellites in view it will require
     //! multiple sentences.
     //!
+    //! GsvAssembler puts the messages of a set back together, one sentence at a time, keeping a
+    //! set for each talker so GPGSV and GLGSV messages can be mixed.
 
+    use heapless::{String, Vec};
     use serde::{Serialize, Deserialize};
 
+    use crate::open_gps::gps::MAX_SENTENCE_LENGTH;
+
+    use super::parse_nmea::*;
+    use super::satellite::SatelliteId;
+    use super::talker::Talker;
+    use super::write_nmea::SentenceWriter;
+
+    /// Most satellites a single GSV sentence can hold.
+    pub const SATELLITES_PER_SENTENCE: usize = 4;
+
+    /// Most messages in a set. The message number is a single digit, so NMEA allows 9.
+    pub const MAX_MESSAGES: usize = 9;
+
+    /// Most satellites a full set of GSV sentences can hold: 9 messages of 4 satellites.
+    pub const MAX_SATELLITES: usize = MAX_MESSAGES * SATELLITES_PER_SENTENCE;
+
+    /// Unfinished sets a GsvAssembler keeps by default, as the Gps and AsyncGps do. A gps sends
+    /// each set in one go, so two is room for one set to be cut into by another.
+    pub const DEFAULT_SETS: usize = 2;
+
     /// The struct for a single satellite. To be accessed as a vector.
-    /// - id -> The satellite id number. 1-32 normally, 193-195 for QZSS (japanese).
+    /// - id -> The [SatelliteId](nmea/satellite/struct.SatelliteId.html), the same as in GsaData
+    ///   satellites.
     /// - elevation -> Elevation of the satellite in degrees
     /// - azimuth -> The degrees from north the satellite is, if it was on the ground.
     /// - snr -> Signal to Noise ratio: Signal / Noise , 0-99, null if not tracking.
     #[derive(PartialEq, Debug, Default, Serialize, Deserialize, Clone)]
     pub struct Satellites {
-        pub id: Option<i32>,
+        pub id: Option<SatelliteId>,
         pub elevation: Option<f32>,
         pub azimuth: Option<f32>,
         pub snr: Option<f32>,
     }
 
-    pub fn parse_gsv(args: Vec<&str>) -> Vec<Satellites> {
-        //! Format $GPGSV, Number of messages, Message number, Sats in view,
-        //!      sat ID, Sat elevation, Sat Azimuth, Sat SNE, Repeat 4 times, *checksum
-        //!
-        //! Sentences can vary in length.
+    /// # GsvData
+    /// A full set of GSV messages from one talker.
+    /// - satellites_in_view: The number of satellites the talker says are in view. Can be more than
+    ///   the satellites given.
+    /// - satellites: [Satellites](nmea/gsv/struct.Satellites.html)
+    /// - signal_id: The NMEA 4.10 signal the set is for, eg 1 for GPS L1 C/A. None before NMEA 4.10.
+    #[derive(PartialEq, Debug, Default, Serialize, Deserialize, Clone)]
+    pub struct GsvData {
+        pub satellites_in_view: i32,
+        pub satellites: Vec<Satellites, MAX_SATELLITES>,
+        pub signal_id: Option<i32>,
+    }
+
+    /// A single GSV sentence.
+    #[derive(PartialEq, Debug, Default, Clone)]
+    pub struct GsvMessage {
+        pub number_of_messages: i32,
+        pub message_number: i32,
+        pub satellites_in_view: i32,
+        pub satellites: Vec<Satellites, SATELLITES_PER_SENTENCE>,
+        pub signal_id: Option<i32>,
+    }
+
+    impl GsvData {
+        /// The set as GSV sentences from the talker, 4 satellites to a sentence. A set with no
+        /// satellites is one sentence.
+        pub fn to_sentences(&self, talker: Talker) -> Vec<String<MAX_SENTENCE_LENGTH>, MAX_MESSAGES> {
+            let number_of_messages = self.satellites.len().div_ceil(SATELLITES_PER_SENTENCE).max(1);
+            let mut sentences = Vec::new();
+            for message_number in 0..number_of_messages {
+                let message = GsvMessage {
+                    number_of_messages: number_of_messages as i32,
+                    message_number: message_number as i32 + 1,
+                    satellites_in_view: self.satellites_in_view,
+                    satellites: self.satellites.iter().skip(message_number * SATELLITES_PER_SENTENCE)
+                        .take(SATELLITES_PER_SENTENCE).cloned().collect(),
+                    signal_id: self.signal_id,
+                };
+                let _ = sentences.push(message.to_sentence(talker));
+            }
+            sentences
+        }
+    }
+
+    impl GsvMessage {
+        /// The GSV sentence from the talker. The signal ID is only added when it's given.
+        pub fn to_sentence(&self, talker: Talker) -> String<MAX_SENTENCE_LENGTH> {
+            let mut sentence = SentenceWriter::new(talker, "GSV");
+            sentence.push_field(self.number_of_messages);
+            sentence.push_field(self.message_number);
+            sentence.push_field(self.satellites_in_view);
+            for satellite in self.satellites.iter() {
+                sentence.push_number(satellite.id.map(|id| id.prn));
+                sentence.push_number(satellite.elevation);
+                sentence.push_number(satellite.azimuth);
+                sentence.push_number(satellite.snr);
+            }
+            if self.signal_id.is_some() {
+                sentence.push_number(self.signal_id);
+            }
+            sentence.finish()
+        }
+    }
+
+    pub fn parse_gsv(fields: Fields) -> Result<Vec<Satellites, SATELLITES_PER_SENTENCE>, NmeaError> {
+        Ok(parse_gsv_message(fields)?.satellites)
+    }
+
+    pub fn parse_gsv_message(mut fields: Fields) -> Result<GsvMessage, NmeaError> {
+        //! Format $GPGSV, Number of messages, Message number, Sats in view,
+        //!      sat ID, Sat elevation, Sat Azimuth, Sat SNE, Repeat 4 times, *checksum
+        //!
+        //! Sentences can vary in length.
         //!
         //! A single GSV string can hold 4 satellites worth of data.
         //!
@@ -350,37 +1185,140 @@ pub mod gsv {
         //!
         //! $GPGSV,1,1,00*79 if no satellites are in view.
         //!
-        //! Max of 4 messages so 16 total satellites.
-        //!
-        //! Assumes that the sentences will always come one after another, I can just read the next sentences.
+        //! NMEA 4.10 adds a signal ID after the satellites. The satellites are groups of 4 fields,
+        //! so an odd one out at the end is the signal ID.
 
-        let header = args.get(0).unwrap();
-        if &header[3..6] != "GSV" {
-            panic!(
-                "Incorrect sentence header. Should be GSV, it is {}",
-                header
-            )
+        let talker = fields.talker();
+        fields.check_header("GSV")?;
+        let number_of_messages: i32 = fields.next_required()?;
+        let message_number: i32 = fields.next_required()?;
+        let satellites_in_view: i32 = fields.next_required()?;
+        let remaining = fields.clone().count();
+        let mut satellites = Vec::new();
+        // A satellite is only given when all 4 of its fields are.
+        for _satellite in 0..(remaining / 4).min(SATELLITES_PER_SENTENCE) {
+            let _ = satellites.push(parse_sat(&mut fields, talker)?);
         }
-        let mut values = Vec::new();
-        let _meta = &args.get(0..4);
-        let sat1 = &args.get(4..8);
-        let sat2 = &args.get(8..12);
-        let sat3 = &args.get(12..16);
-        let sat4 = &args.get(16..20);
-        for sat in &[sat1, sat2, sat3, sat4] {
-            if sat.is_some() {
-                values.push(parse_sat(sat.unwrap()))
-            }
+        let signal_id: Option<i32> = match remaining % 4 {
+            1 => fields.skip_fields(remaining - 1 - 4 * satellites.len())
+                .and_then(|_| fields.next_i32())?,
+            _ => None,
+        };
+        Ok(GsvMessage { number_of_messages, message_number, satellites_in_view, satellites, signal_id })
+    }
+
+    fn parse_sat(fields: &mut Fields, talker: Talker) -> Result<Satellites, NmeaError> {
+        Ok(Satellites {
+            id: fields.next_number()?.map(|prn| SatelliteId::from_prn(talker, prn)),
+            elevation: fields.next_f32()?,
+            azimuth: fields.next_f32()?,
+            snr: fields.next_f32()?,
+        })
+    }
+
+    /// A set that is still missing messages.
+    #[derive(Debug, Clone)]
+    struct PartialSet {
+        talker: Talker,
+        signal_id: Option<i32>,
+        number_of_messages: i32,
+        next_message: i32,
+        data: GsvData,
+    }
+
+    /// Puts GSV messages together into full sets, one message at a time.
+    ///
+    /// Each talker has a set for each NMEA 4.10 signal ID, so the sets of two signals can be mixed.
+    /// SETS (at least 1) is how many unfinished sets are kept at once, past it the oldest is dropped. A set is
+    /// about 1 KB, so keep it small on a microcontroller: `GsvAssembler::<4>::default()` mixes 4.
+    /// - A first message always starts a new set for its talker, dropping an unfinished one.
+    /// - Any other message has to be the next one of the talker's set, with the same number of
+    ///   messages. If it isn't, the set is dropped and push gives GsvSequence.
+    /// - A set of more than MAX_MESSAGES messages isn't NMEA, it is dropped and push gives
+    ///   GsvSequence. So every satellite of a set fits in GsvData.
+    /// - The set is given once its last message is pushed.
+    /// ```ignore
+    /// let mut assembler = GsvAssembler::new();
+    /// for sentence in framer.feed(&bytes) {
+    ///     let fields = parse_sentence(&sentence).unwrap();
+    ///     if let Some(gsv) = assembler.push(fields.talker(), parse_gsv_message(fields)?)? {
+    ///         println!("{} satellites in view", gsv.satellites_in_view);
+    ///     }
+    /// }
+    /// ```
+    #[derive(Debug, Default, Clone)]
+    pub struct GsvAssembler<const SETS: usize = DEFAULT_SETS> {
+        sets: Vec<PartialSet, SETS>,
+    }
+
+    impl GsvAssembler {
+        /// An assembler keeping DEFAULT_SETS.
+        pub const fn new() -> GsvAssembler {
+            GsvAssembler { sets: Vec::new() }
         }
-        values
     }
 
-    fn parse_sat(args: &[&str]) -> Satellites {
-        Satellites {
-            id: args.get(0).unwrap().parse().ok(),
-            elevation: args.get(1).unwrap().parse().ok(),
-            azimuth: args.get(2).unwrap().parse().ok(),
-            snr: args.get(3).unwrap().parse().ok(),
+    impl<const SETS: usize> GsvAssembler<SETS> {
+        /// Adds a message from the talker, giving the full set if it was the last one.
+        pub fn push(&mut self, talker: Talker, message: GsvMessage) -> Result<Option<GsvData>, NmeaError> {
+            let signal_id = message.signal_id;
+            let position = self.sets.iter().position(|set| set.talker == talker && set.signal_id == signal_id);
+            if message.number_of_messages > MAX_MESSAGES as i32 {
+                if let Some(index) = position {
+                    self.sets.swap_remove(index);
+                }
+                return Err(NmeaError::GsvSequence);
+            }
+            let index = if message.message_number == 1 && message.number_of_messages >= 1 {
+                let data = GsvData { signal_id, ..GsvData::default() };
+                let set = PartialSet { talker, signal_id, number_of_messages: message.number_of_messages, next_message: 1, data };
+                match position {
+                    Some(index) => {
+                        self.sets[index] = set;
+                        index
+                    }
+                    None => {
+                        if self.sets.is_full() {
+                            self.sets.remove(0); // The oldest set.
+                        }
+                        let _ = self.sets.push(set);
+                        self.sets.len() - 1
+                    }
+                }
+            } else {
+                match position {
+                    Some(index) if self.sets[index].next_message == message.message_number
+                        && self.sets[index].number_of_messages == message.number_of_messages => index,
+                    _ => {
+                        if let Some(index) = position {
+                            self.sets.swap_remove(index);
+                        }
+                        return Err(NmeaError::GsvSequence);
+                    }
+                }
+            };
+
+            let set = &mut self.sets[index];
+            set.data.satellites_in_view = message.satellites_in_view;
+            for satellite in message.satellites {
+                let _ = set.data.satellites.push(satellite); // At most MAX_MESSAGES of 4, so it fits.
+            }
+            set.next_message += 1;
+            if message.message_number == set.number_of_messages {
+                Ok(Some(self.sets.swap_remove(index).data))
+            } else {
+                Ok(None)
+            }
+        }
+
+        /// Satellites in view given by the talker's first unfinished set. None if there isn't one.
+        pub fn satellites_in_view(&self, talker: Talker) -> Option<i32> {
+            self.sets.iter().find(|set| set.talker == talker).map(|set| set.data.satellites_in_view)
+        }
+
+        /// Drops all unfinished sets.
+        pub fn reset(&mut self) {
+            self.sets.clear();
         }
     }
 }
@@ -389,55 +1327,106 @@ pub mod rmc {
     //! # Recommended Minimum data
     //!
     //! Gives UTC, latitude, longitude, Speed, True course, Magnetic course, Date, Magnatic variation
-    use super::parse_nmea::*;
+    use heapless::String;
     use serde::{Serialize, Deserialize};
 
+    use crate::open_gps::gps::MAX_SENTENCE_LENGTH;
+
+    use super::datetime::{NmeaDate, NmeaDateTime, NmeaTime};
+    use super::gns::{Mode, NavStatus};
+    use super::parse_nmea::*;
+    use super::talker::Talker;
+    use super::write_nmea::SentenceWriter;
+
     /// # RmcData
-    /// - utc: UTC
+    /// - utc: UTC [NmeaTime](nmea/datetime/struct.NmeaTime.html)
     /// - fix_status: Is there a fix with some satellites? True/False
     /// - latitude
     /// - longitude
     /// - speed: in Knots
     /// - course: Track angle in degrees against true north.
-    /// - data: the date as a string. ddmmyy.
+    /// - date: [NmeaDate](nmea/datetime/struct.NmeaDate.html)
     /// - mag_var: Magnetic variation between true north and magnetic north.
+    /// - mode: [Mode](nmea/gns/enum.Mode.html), None before NMEA 2.3.
+    /// - nav_status: [NavStatus](nmea/gns/enum.NavStatus.html), None before NMEA 4.10.
     #[derive(PartialEq, Debug, Default, Serialize, Deserialize, Clone)]
     pub struct RmcData {
-        pub utc: f64,
+        pub utc: Option<NmeaTime>,
         pub fix_status: bool,
-        pub latitude: Option<f32>,
-        pub longitude: Option<f32>,
+        pub latitude: Option<f64>,
+        pub longitude: Option<f64>,
         pub speed: Option<f32>,
         pub course: Option<f32>,
-        pub date: String,
+        pub date: Option<NmeaDate>,
         pub mag_var: Option<f32>,
+        pub mode: Option<Mode>,
+        pub nav_status: Option<NavStatus>,
     }
 
-    pub fn parse_rmc(args: Vec<&str>) -> RmcData {
+    impl RmcData {
+        /// The full UTC timestamp, None if the time or date isn't given.
+        pub fn datetime(&self) -> Option<NmeaDateTime> {
+            Some(NmeaDateTime { date: self.date?, time: self.utc? })
+        }
+
+        /// The RMC sentence from the talker. The nav status is only added when it's given.
+        pub fn to_sentence(&self, talker: Talker) -> String<MAX_SENTENCE_LENGTH> {
+            let mut sentence = SentenceWriter::new(talker, "RMC");
+            sentence.push_time(self.utc);
+            sentence.push_field(if self.fix_status { "A" } else { "V" });
+            sentence.push_lat(self.latitude);
+            sentence.push_long(self.longitude);
+            sentence.push_number(self.speed);
+            sentence.push_number(self.course);
+            sentence.push_date(self.date);
+            match self.mag_var {
+                Some(mag_var) => {
+                    sentence.push_field(mag_var.abs());
+                    sentence.push_field(if mag_var < 0.0 { "W" } else { "E" });
+                }
+                None => sentence.push_field(","),
+            }
+            sentence.push_number(self.mode.map(Mode::to_char));
+            if let Some(nav_status) = self.nav_status {
+                sentence.push_field(nav_status.to_char());
+            }
+            sentence.finish()
+        }
+    }
+
+    pub fn parse_rmc(mut fields: Fields) -> Result<RmcData, NmeaError> {
         //! Magnetic variation, positive is east, negative is west.
         //! Data string format:
         //!   0     1         2       3           4       5       6           7       8           9
         //! $GPRMC,UTC, Fix status, Lat, NS indicator, Long, EW indicator, Speed, Course (true), date,
-        //!         10                           11                  12
-        //! magnetic variation (degrees), magnetic variation (E/W), Mode * checksum
+        //!         10                           11                  12          13
+        //! magnetic variation (degrees), magnetic variation (E/W), Mode, Nav status * checksum
+        //!
+        //! Mode is only given from NMEA 2.3, nav status from NMEA 4.10.
 
-        let utc = args.get(1).unwrap().parse().unwrap_or(0.0);
-        let fix_status = match *args.get(2).unwrap_or(&"V") {
-            "A" => true,
-            "V" => false,
+        fields.check_header("RMC")?;
+        let utc: Option<NmeaTime> = fields.next_number()?;
+        let fix_status = match fields.next_char()? {
+            Some('A') => true,
+            Some('V') => false,
             _ => false,
         };
-        let latitude: Option<f32> = _parse_degrees(args.get(3).unwrap(), args.get(4).unwrap());
-        let longitude: Option<f32> = _parse_degrees(args.get(5).unwrap(), args.get(6).unwrap());
-        let speed: Option<f32> = args.get(7).unwrap().parse::<f32>().ok();
-        let course: Option<f32> = args.get(8).unwrap().parse::<f32>().ok();
-        let date: String = args.get(9).unwrap_or(&"").to_string();
-        let mag_var: Option<f32> = match *args.get(12).unwrap_or(&"") {
-            "E" => args.get(11).unwrap().parse::<f32>().ok(),
-            "W" => Some(args.get(11).unwrap().parse::<f32>().unwrap() * -1.0),
+        let latitude: Option<f64> = fields.next_lat()?;
+        let longitude: Option<f64> = fields.next_long()?;
+        let speed: Option<f32> = fields.next_f32()?;
+        let course: Option<f32> = fields.next_f32()?;
+        // The date can be left off.
+        let date: Option<NmeaDate> = fields.next_trailing()?;
+        // Magnetic variation and its direction can be left off.
+        let mut mag_var_fields = fields.clone();
+        let mag_var: Option<f32> = match fields.nth(1).unwrap_or("") {
+            "E" => mag_var_fields.next_f32()?,
+            "W" => mag_var_fields.next_f32()?.map(|mag_var| -mag_var),
             _ => None,
         };
-        return RmcData {
+        let mode = fields.next().and_then(|mode| mode.chars().next()).map(Mode::from_char);
+        let nav_status = NavStatus::from_field(fields.next());
+        Ok(RmcData {
             utc,
             fix_status,
             latitude,
@@ -446,7 +1435,9 @@ pub mod rmc {
             course,
             date,
             mag_var,
-        };
+            mode,
+            nav_status,
+        })
     }
 }
 
@@ -455,149 +1446,701 @@ pub mod vtg {
     //!
     //! Gives course headings and speed data.
 
+    use heapless::String;
     use serde::{Serialize, Deserialize};
 
-    #[derive(PartialEq, Debug, Serialize, Deserialize, Clone, Default)]
-    pub enum Mode {
-        Autonomous,
-        Differential,
-        Estimated,
-        #[default]
-        Unknown,
-    }
+    use crate::open_gps::gps::MAX_SENTENCE_LENGTH;
+
+    use super::gns::Mode;
+    use super::parse_nmea::*;
+    use super::talker::Talker;
+    use super::write_nmea::SentenceWriter;
 
     /// # VtgData
     /// - true_course: Course in degrees against true north.
     /// - magnetic_course: Course in degrees against magnetic north
     /// - speed_knots
     /// - speed_kpg
-    /// - mode: [Mode (enum)](nmea/vtg/enum.Mode.html)
+    /// - mode: [Mode](nmea/gns/enum.Mode.html), None before NMEA 2.3.
     #[derive(PartialEq, Debug, Default, Deserialize, Serialize, Clone)]
     pub struct VtgData {
         pub true_course: Option<f32>,
         pub magnetic_course: Option<f32>,
         pub speed_knots: Option<f32>,
         pub speed_kph: Option<f32>,
-        pub mode: Mode,
+        pub mode: Option<Mode>,
     }
 
-    pub fn parse_vtg(args: Vec<&str>) -> VtgData {
+    impl VtgData {
+        /// The VTG sentence from the talker. Mode None is written as an empty field.
+        pub fn to_sentence(&self, talker: Talker) -> String<MAX_SENTENCE_LENGTH> {
+            let mut sentence = SentenceWriter::new(talker, "VTG");
+            sentence.push_number(self.true_course);
+            sentence.push_field("T");
+            sentence.push_number(self.magnetic_course);
+            sentence.push_field("M");
+            sentence.push_number(self.speed_knots);
+            sentence.push_field("N");
+            sentence.push_number(self.speed_kph);
+            sentence.push_field("K");
+            sentence.push_number(self.mode.map(Mode::to_char));
+            sentence.finish()
+        }
+    }
+
+    pub fn parse_vtg(mut fields: Fields) -> Result<VtgData, NmeaError> {
         //! Sentence format
         //!
         //! $GPVTG,  course, reference (True), course, reference (magnetic), Speed, knots,
         //! speed, kph, mode.
-        let true_course: Option<f32> = args.get(1).unwrap().parse::<f32>().ok();
-        let magnetic_course: Option<f32> = args.get(3).unwrap().parse::<f32>().ok();
-        let speed_knots: Option<f32> = args.get(5).unwrap().parse::<f32>().ok();
-        let speed_kph: Option<f32> = args.get(7).unwrap().parse::<f32>().ok();
-
-        let mode = match *args.get(9).unwrap_or(&"N") {
-            "A" => Mode::Autonomous,
-            "D" => Mode::Differential,
-            "E" => Mode::Estimated,
-            _ => Mode::Unknown,
-        };
-        return VtgData {
+        fields.check_header("VTG")?;
+        let true_course: Option<f32> = fields.next_f32()?;
+        fields.skip_fields(1)?; // T
+        let magnetic_course: Option<f32> = fields.next_f32()?;
+        fields.skip_fields(1)?; // M
+        let speed_knots: Option<f32> = fields.next_f32()?;
+        fields.skip_fields(1)?; // N
+        let speed_kph: Option<f32> = fields.next_f32()?;
+
+        // K, then the mode that can be left off.
+        let mode = fields.nth(1).and_then(|mode| mode.chars().next()).map(Mode::from_char);
+        Ok(VtgData {
             true_course,
             magnetic_course,
             speed_knots,
             speed_kph,
             mode,
-        };
+        })
     }
 }
 
 pub mod gll {
     //! # Longitude and Latitude data only
-    use super::parse_nmea::*;
+    use heapless::String;
     use serde::{Serialize, Deserialize};
 
+    use crate::open_gps::gps::MAX_SENTENCE_LENGTH;
+
+    use super::datetime::NmeaTime;
+    use super::gns::Mode;
+    use super::parse_nmea::*;
+    use super::talker::Talker;
+    use super::write_nmea::SentenceWriter;
+
     /// # GllData
     /// - latitude
     /// - longitude
-    /// - utc
+    /// - utc: [NmeaTime](nmea/datetime/struct.NmeaTime.html)
     /// - is_valid: Is there a satellite signal? True / false
+    /// - mode: [Mode](nmea/gns/enum.Mode.html), None before NMEA 2.3.
     #[derive(PartialEq, Debug, Default, Serialize, Deserialize, Clone)]
     pub struct GllData {
-        pub latitude: Option<f32>,
-        pub longitude: Option<f32>,
-        pub utc: Option<f64>,
+        pub latitude: Option<f64>,
+        pub longitude: Option<f64>,
+        pub utc: Option<NmeaTime>,
         pub is_valid: bool,
+        pub mode: Option<Mode>,
+    }
+
+    impl GllData {
+        /// The GLL sentence from the talker.
+        pub fn to_sentence(&self, talker: Talker) -> String<MAX_SENTENCE_LENGTH> {
+            let mut sentence = SentenceWriter::new(talker, "GLL");
+            sentence.push_lat(self.latitude);
+            sentence.push_long(self.longitude);
+            sentence.push_time(self.utc);
+            sentence.push_field(if self.is_valid { "A" } else { "V" });
+            sentence.push_number(self.mode.map(Mode::to_char));
+            sentence.finish()
+        }
     }
 
-    pub fn parse_gll(args: Vec<&str>) -> GllData {
+    pub fn parse_gll(mut fields: Fields) -> Result<GllData, NmeaError> {
         // Format for the gpgll data string:
         // [1] Latitude(as hhmm.mmm),
         // [2] Latitude North or South,
         // [3] Longitude(as hhmm.mmm),
         // [4] Longitude North or South,
         // [5] Time as hhmmss.ss,
-        // [6] A
-        // [7] A
+        // [6] A valid or V not valid
+        // [7] Mode, from NMEA 2.3
 
+        fields.check_header("GLL")?;
         // Parse Latitude.
-
-        let latitude: Option<f32> = _parse_degrees(args.get(1).unwrap(), args.get(2).unwrap());
-        let longitude: Option<f32> = _parse_degrees(args.get(3).unwrap(), args.get(4).unwrap());
+        let latitude: Option<f64> = fields.next_lat()?;
+        let longitude: Option<f64> = fields.next_long()?;
         // Parse time
-        let utc = args.get(5).unwrap_or(&"0").parse::<f64>().ok();
-        let is_valid = match *args.get(6).unwrap_or(&"") {
+        let utc: Option<NmeaTime> = match fields.clone().next() {
+            Some(_utc) => fields.next_number()?,
+            None => None,
+        };
+        let is_valid = match fields.next().unwrap_or("") {
             "A" => true,
             "V" => false,
             _ => false,
         };
-        return GllData {
+        let mode = fields.next().and_then(|mode| mode.chars().next()).map(Mode::from_char);
+        Ok(GllData {
             latitude,
             longitude,
             utc,
             is_valid,
+            mode,
+        })
+    }
+}
+
+pub mod gns {
+    //! # GNSS fix data
+    //!
+    //! Like GGA, but with a mode for each constellation. Given as $GNGNS when more than one
+    //! constellation is used for the fix.
+    use heapless::{String, Vec};
+    use serde::{Serialize, Deserialize};
+
+    use crate::open_gps::gps::MAX_SENTENCE_LENGTH;
+
+    use super::datetime::NmeaTime;
+    use super::parse_nmea::*;
+    use super::talker::Talker;
+    use super::write_nmea::SentenceWriter;
+
+    /// Most constellations a GNS gives a mode for: GPS, GLONASS, Galileo, BeiDou, QZSS, NavIC.
+    pub const MAX_SYSTEMS: usize = 6;
+
+    /// Mode of a constellation. Also the mode RMC, GLL and VTG give, and the same fix as a GGA
+    /// [SatFix](../gga/enum.SatFix.html).
+    /// - NoFix -> N
+    /// - Autonomous -> A
+    /// - Differential -> D
+    /// - Precise -> P
+    /// - RtkFixed -> R, real time kinematic with fixed integers.
+    /// - RtkFloat -> F, real time kinematic with floating integers.
+    /// - Estimated -> E, dead reckoning.
+    /// - Manual -> M
+    /// - Simulator -> S
+    /// - Unknown -> Any other letter.
+    #[derive(PartialEq, Debug, Serialize, Deserialize, Clone, Copy, Default)]
+    pub enum Mode {
+        NoFix,
+        Autonomous,
+        Differential,
+        Precise,
+        RtkFixed,
+        RtkFloat,
+        Estimated,
+        Manual,
+        Simulator,
+        #[default]
+        Unknown,
+    }
+
+    impl Mode {
+        pub(crate) fn from_char(mode: char) -> Mode {
+            match mode {
+                'N' => Mode::NoFix,
+                'A' => Mode::Autonomous,
+                'D' => Mode::Differential,
+                'P' => Mode::Precise,
+                'R' => Mode::RtkFixed,
+                'F' => Mode::RtkFloat,
+                'E' => Mode::Estimated,
+                'M' => Mode::Manual,
+                'S' => Mode::Simulator,
+                _ => Mode::Unknown,
+            }
+        }
+
+        /// Unknown is written as N, no fix.
+        pub(crate) fn to_char(self) -> char {
+            match self {
+                Mode::NoFix | Mode::Unknown => 'N',
+                Mode::Autonomous => 'A',
+                Mode::Differential => 'D',
+                Mode::Precise => 'P',
+                Mode::RtkFixed => 'R',
+                Mode::RtkFloat => 'F',
+                Mode::Estimated => 'E',
+                Mode::Manual => 'M',
+                Mode::Simulator => 'S',
+            }
+        }
+    }
+
+    /// NMEA 4.1 navigational status.
+    /// - Safe -> S
+    /// - Caution -> C
+    /// - Unsafe -> U
+    /// - NotValid -> V
+    #[derive(PartialEq, Debug, Serialize, Deserialize, Clone, Copy)]
+    pub enum NavStatus {
+        Safe,
+        Caution,
+        Unsafe,
+        NotValid,
+    }
+
+    impl NavStatus {
+        /// The status of an NMEA 4.1 status field, None if it's left off or not a status.
+        pub fn from_field(status: Option<&str>) -> Option<NavStatus> {
+            match status? {
+                "S" => Some(NavStatus::Safe),
+                "C" => Some(NavStatus::Caution),
+                "U" => Some(NavStatus::Unsafe),
+                "V" => Some(NavStatus::NotValid),
+                _ => None,
+            }
+        }
+
+        pub(crate) fn to_char(self) -> char {
+            match self {
+                NavStatus::Safe => 'S',
+                NavStatus::Caution => 'C',
+                NavStatus::Unsafe => 'U',
+                NavStatus::NotValid => 'V',
+            }
+        }
+    }
+
+    /// # GnsData
+    /// - utc: UTC [NmeaTime](nmea/datetime/struct.NmeaTime.html)
+    /// - lat: Latitude
+    /// - long: Longitude
+    /// - modes: [Mode](nmea/gns/enum.Mode.html) of each constellation, in the order GPS, GLONASS,
+    ///   Galileo, BeiDou, QZSS, NavIC. Only as many as the gps gives.
+    /// - satellites_used: Satellites used for the fix, from all constellations.
+    /// - hdop: Horizontal Dilution of Precision.
+    /// - msl_alt: Altitude against Mean Sea Level in metres.
+    /// - geoidal_sep: Difference between WGS-84 earth ellipsoid and mean sea level in metres.
+    /// - age_diff_corr: Age in seconds since last update from reference station.
+    /// - diff_station_id: Reference station id.
+    /// - nav_status: [NavStatus](nmea/gns/enum.NavStatus.html), None before NMEA 4.1.
+    #[derive(PartialEq, Debug, Default, Serialize, Deserialize, Clone)]
+    pub struct GnsData {
+        pub utc: NmeaTime,
+        pub lat: Option<f64>,
+        pub long: Option<f64>,
+        pub modes: Vec<Mode, MAX_SYSTEMS>,
+        pub satellites_used: i32,
+        pub hdop: Option<f32>,
+        pub msl_alt: Option<f32>,
+        pub geoidal_sep: Option<f32>,
+        pub age_diff_corr: Option<f32>,
+        pub diff_station_id: Option<i32>,
+        pub nav_status: Option<NavStatus>,
+    }
+
+    impl GnsData {
+        /// The mode of one constellation, None if the gps didn't give it.
+        pub fn mode(&self, talker: Talker) -> Option<Mode> {
+            let system = match talker {
+                Talker::Gps => 0,
+                Talker::Glonass => 1,
+                Talker::Galileo => 2,
+                Talker::Beidou => 3,
+                Talker::Qzss => 4,
+                Talker::Gnss | Talker::Unknown => return None,
+            };
+            self.modes.get(system).copied()
+        }
+
+        /// The GNS sentence from the talker. The nav status is only added when it's given.
+        pub fn to_sentence(&self, talker: Talker) -> String<MAX_SENTENCE_LENGTH> {
+            let mut sentence = SentenceWriter::new(talker, "GNS");
+            sentence.push_time(Some(self.utc));
+            sentence.push_lat(self.lat);
+            sentence.push_long(self.long);
+            let modes: String<MAX_SYSTEMS> = self.modes.iter().map(|mode| mode.to_char()).collect();
+            sentence.push_field(modes);
+            sentence.push_field(self.satellites_used);
+            sentence.push_number(self.hdop);
+            sentence.push_number(self.msl_alt);
+            sentence.push_number(self.geoidal_sep);
+            sentence.push_number(self.age_diff_corr);
+            sentence.push_number(self.diff_station_id);
+            if let Some(nav_status) = self.nav_status {
+                sentence.push_field(nav_status.to_char());
+            }
+            sentence.finish()
+        }
+    }
+
+    pub fn parse_gns(mut fields: Fields) -> Result<GnsData, NmeaError> {
+        //! Sentence format
+        //!
+        //! $GNGNS, UTC, lat, N/S, long, E/W, modes, sats used, HDOP, MSL alt, geoidal separation,
+        //! age of diff corr, diff station id, nav status * checksum
+        //!
+        //! Nav status is only given from NMEA 4.1.
+        fields.check_header("GNS")?;
+        let utc: NmeaTime = fields.next_required()?;
+        let lat: Option<f64> = fields.next_lat()?;
+        let long: Option<f64> = fields.next_long()?;
+        let modes: Vec<Mode, MAX_SYSTEMS> = fields.next_field()?.chars().take(MAX_SYSTEMS).map(Mode::from_char).collect();
+        let satellites_used: i32 = fields.next_required()?;
+        let hdop: Option<f32> = fields.next_f32()?;
+        let msl_alt: Option<f32> = fields.next_f32()?;
+        let geoidal_sep: Option<f32> = fields.next_f32()?;
+        let age_diff_corr: Option<f32> = fields.next_f32()?;
+        let diff_station_id: Option<i32> = fields.next_i32()?;
+        let nav_status = NavStatus::from_field(fields.next());
+        Ok(GnsData {
+            utc,
+            lat,
+            long,
+            modes,
+            satellites_used,
+            hdop,
+            msl_alt,
+            geoidal_sep,
+            age_diff_corr,
+            diff_station_id,
+            nav_status,
+        })
+    }
+}
+
+pub mod zda {
+    //! # Date and time
+    //!
+    //! Gives UTC, the full date and the local time zone.
+    use heapless::String;
+    use serde::{Serialize, Deserialize};
+
+    use crate::open_gps::gps::MAX_SENTENCE_LENGTH;
+
+    use super::datetime::{NmeaDate, NmeaDateTime, NmeaTime};
+    use super::parse_nmea::*;
+    use super::talker::Talker;
+    use super::write_nmea::SentenceWriter;
+
+    /// # ZdaData
+    /// - utc: UTC [NmeaTime](nmea/datetime/struct.NmeaTime.html)
+    /// - date: [NmeaDate](nmea/datetime/struct.NmeaDate.html), None if the day, month or year isn't given.
+    /// - local_zone_hours: Hours the local time zone is from UTC, -13 to 13. MTK modules leave it empty.
+    /// - local_zone_minutes: Minutes the local time zone is from UTC, 0 to 59.
+    #[derive(PartialEq, Debug, Default, Serialize, Deserialize, Clone)]
+    pub struct ZdaData {
+        pub utc: NmeaTime,
+        pub date: Option<NmeaDate>,
+        pub local_zone_hours: Option<i32>,
+        pub local_zone_minutes: Option<i32>,
+    }
+
+    impl ZdaData {
+        /// The full UTC timestamp, None if the date isn't given.
+        pub fn datetime(&self) -> Option<NmeaDateTime> {
+            Some(NmeaDateTime { date: self.date?, time: self.utc })
+        }
+
+        /// The ZDA sentence from the talker.
+        pub fn to_sentence(&self, talker: Talker) -> String<MAX_SENTENCE_LENGTH> {
+            let mut sentence = SentenceWriter::new(talker, "ZDA");
+            sentence.push_time(Some(self.utc));
+            match self.date {
+                Some(date) => {
+                    sentence.push_field(format_args!("{:02}", date.day));
+                    sentence.push_field(format_args!("{:02}", date.month));
+                    sentence.push_field(date.year);
+                }
+                None => sentence.push_field(",,"),
+            }
+            sentence.push_number(self.local_zone_hours);
+            sentence.push_number(self.local_zone_minutes);
+            sentence.finish()
+        }
+    }
+
+    pub fn parse_zda(mut fields: Fields) -> Result<ZdaData, NmeaError> {
+        //! Sentence format
+        //!
+        //! $GPZDA, UTC, day, month, year, local zone hours, local zone minutes *checksum
+        fields.check_header("ZDA")?;
+        let utc: NmeaTime = fields.next_required()?;
+        let day_index = fields.index();
+        let day: Option<u8> = fields.next_number()?;
+        let month: Option<u8> = fields.next_number()?;
+        let year: Option<u16> = fields.next_number()?;
+        let date: Option<NmeaDate> = match (year, month, day) {
+            (Some(year), Some(month), Some(day)) => Some(NmeaDate::new(year, month, day).ok_or(NmeaError::BadNumber(day_index))?),
+            _ => None,
         };
+        let local_zone_hours: Option<i32> = fields.next_i32()?;
+        let local_zone_minutes: Option<i32> = fields.next_i32()?;
+        Ok(ZdaData {
+            utc,
+            date,
+            local_zone_hours,
+            local_zone_minutes,
+        })
+    }
+}
+
+pub mod gst {
+    //! # Pseudorange error statistics
+    //!
+    //! The gps's own estimate of how far off its fix is, in metres.
+    use heapless::String;
+    use serde::{Serialize, Deserialize};
+
+    use crate::open_gps::gps::MAX_SENTENCE_LENGTH;
+
+    use super::datetime::NmeaTime;
+    use super::parse_nmea::*;
+    use super::talker::Talker;
+    use super::write_nmea::SentenceWriter;
+
+    /// # GstData
+    /// - utc: UTC of the fix the statistics are for, [NmeaTime](nmea/datetime/struct.NmeaTime.html).
+    /// - rms: RMS of the standard deviation of the pseudoranges.
+    /// - semi_major: Standard deviation of the semi-major axis of the error ellipse, metres.
+    /// - semi_minor: Standard deviation of the semi-minor axis of the error ellipse, metres.
+    /// - orientation: Orientation of the semi-major axis, degrees from true north.
+    /// - lat_std_dev: Standard deviation of latitude error, metres.
+    /// - long_std_dev: Standard deviation of longitude error, metres.
+    /// - alt_std_dev: Standard deviation of altitude error, metres.
+    #[derive(PartialEq, Debug, Default, Serialize, Deserialize, Clone)]
+    pub struct GstData {
+        pub utc: NmeaTime,
+        pub rms: Option<f32>,
+        pub semi_major: Option<f32>,
+        pub semi_minor: Option<f32>,
+        pub orientation: Option<f32>,
+        pub lat_std_dev: Option<f32>,
+        pub long_std_dev: Option<f32>,
+        pub alt_std_dev: Option<f32>,
+    }
+
+    impl GstData {
+        /// The GST sentence from the talker.
+        pub fn to_sentence(&self, talker: Talker) -> String<MAX_SENTENCE_LENGTH> {
+            let mut sentence = SentenceWriter::new(talker, "GST");
+            sentence.push_time(Some(self.utc));
+            for statistic in [self.rms, self.semi_major, self.semi_minor, self.orientation,
+                self.lat_std_dev, self.long_std_dev, self.alt_std_dev].iter() {
+                sentence.push_number(*statistic);
+            }
+            sentence.finish()
+        }
+    }
+
+    pub fn parse_gst(mut fields: Fields) -> Result<GstData, NmeaError> {
+        //! Sentence format
+        //!
+        //! $GPGST, UTC, RMS, semi-major, semi-minor, orientation, lat std dev, long std dev,
+        //! alt std dev *checksum
+        fields.check_header("GST")?;
+        let utc: NmeaTime = fields.next_required()?;
+        let rms: Option<f32> = fields.next_f32()?;
+        let semi_major: Option<f32> = fields.next_f32()?;
+        let semi_minor: Option<f32> = fields.next_f32()?;
+        let orientation: Option<f32> = fields.next_f32()?;
+        let lat_std_dev: Option<f32> = fields.next_f32()?;
+        let long_std_dev: Option<f32> = fields.next_f32()?;
+        let alt_std_dev: Option<f32> = fields.next_f32()?;
+        Ok(GstData {
+            utc,
+            rms,
+            semi_major,
+            semi_minor,
+            orientation,
+            lat_std_dev,
+            long_std_dev,
+            alt_std_dev,
+        })
     }
 }
 
 #[cfg(test)]
 mod nmea_tests {
+    use core::fmt::{Display, Write};
+
+    use heapless::String;
+
+    /// to_string without std.
+    fn display(value: impl Display) -> String<64> {
+        let mut string = String::new();
+        write!(string, "{}", value).unwrap();
+        string
+    }
 
     mod parse_nmea {
         use crate::nmea::parse_nmea;
+        use crate::nmea::parse_nmea::{Fields, NmeaError};
+        use crate::nmea::talker::Talker;
 
         #[test]
         fn parse_degrees() {
-            assert_eq!(parse_nmea::_parse_degrees("1020.12345", "N").unwrap(),
-                       10.335391);
-            assert_eq!(parse_nmea::_parse_degrees("11020.12345", "N").unwrap(),
-                       110.335391);
+            assert_eq!(parse_nmea::_parse_degrees("1020.12345").unwrap(),
+                       10.0 + 20.12345 / 60.0);
+            assert_eq!(parse_nmea::_parse_degrees("11020.12345").unwrap(),
+                       110.0 + 20.12345 / 60.0);
+            // 1e-7 degrees, 0.000006 minutes, is kept.
+            assert_eq!(parse_nmea::_parse_degrees("5132.731400").unwrap(), 51.0 + 32.7314 / 60.0);
+            let step = parse_nmea::_parse_degrees("5132.731406").unwrap() - parse_nmea::_parse_degrees("5132.731400").unwrap();
+            assert!((step - 1e-7).abs() < 1e-12);
+        }
+
+        #[test]
+        fn lat_long() {
+            let mut fields = Fields::new("$GPGLL,1020.12345,S,11020.12345,E,,,1020.12345,X,10x0.1,N,1");
+            fields.skip_fields(1).unwrap();
+            assert_eq!(fields.next_lat(), Ok(Some(-(10.0 + 20.12345 / 60.0))));
+            assert_eq!(fields.next_long(), Ok(Some(110.0 + 20.12345 / 60.0)));
+            assert_eq!(fields.next_lat(), Ok(None));
+            assert_eq!(fields.next_long(), Err(NmeaError::BadDirection(8)));
+            assert_eq!(fields.next_lat(), Err(NmeaError::BadNumber(9)));
+            assert_eq!(fields.next_lat(), Err(NmeaError::MissingField(12)));
+        }
+
+        #[test]
+        fn lat_long_out_of_range() {
+            let mut fields = Fields::new("$GPGLL,9100.0000,N,18000.0100,E,9000.0000,S,18000.0000,W");
+            fields.skip_fields(1).unwrap();
+            assert_eq!(fields.next_lat(), Err(NmeaError::BadNumber(1)));
+            assert_eq!(fields.next_long(), Err(NmeaError::BadNumber(3)));
+            assert_eq!(fields.next_lat(), Ok(Some(-90.0)));
+            assert_eq!(fields.next_long(), Ok(Some(-180.0)));
+        }
+
+        #[test]
+        fn minutes_out_of_range() {
+            // 75 minutes and -5 minutes.
+            let mut fields = Fields::new("$GPGLL,1075.0,N,010-5.0,E,1059.9999,N,00000.0,E");
+            fields.skip_fields(1).unwrap();
+            assert_eq!(fields.next_lat(), Err(NmeaError::BadNumber(1)));
+            assert_eq!(fields.next_long(), Err(NmeaError::BadNumber(3)));
+            assert_eq!(fields.next_lat(), Ok(Some(10.0 + 59.9999 / 60.0)));
+            assert_eq!(fields.next_long(), Ok(Some(0.0)));
+            assert_eq!(parse_nmea::_parse_degrees("1060.0"), None);
+        }
+
+        #[test]
+        fn numbers_and_chars() {
+            let mut fields = Fields::new("$GPGGA,1.5,,x,A");
+            assert_eq!(fields.sentence_type(), Some("GGA"));
+            assert_eq!(fields.talker(), Talker::Gps);
+            assert!(fields.check_header("GGA").is_ok());
+            assert_eq!(fields.clone().next_required::<f32>(), Ok(1.5));
+            assert_eq!(fields.next_f32(), Ok(Some(1.5)));
+            assert_eq!(fields.clone().next_required::<f32>(), Err(NmeaError::MissingField(2)));
+            assert_eq!(fields.next_f32(), Ok(None));
+            assert_eq!(fields.next_i32(), Err(NmeaError::BadNumber(3)));
+            assert_eq!(fields.index(), 4);
+            assert_eq!(fields.next_char(), Ok(Some('A')));
+            assert_eq!(fields.next_char(), Err(NmeaError::MissingField(5)));
+            assert_eq!(fields.next(), None);
+        }
+
+        #[test]
+        fn sentence_fields() {
+            let fields = parse_nmea::parse_sentence("$GPVTG,89.68,T,,M,0.00,N,0.0,K*5F\r\n").unwrap();
+            assert_eq!(fields.collect::<heapless::Vec<&str, 16>>(), ["$GPVTG", "89.68", "T", "", "M", "0.00", "N", "0.0", "K"]);
+            assert!(parse_nmea::parse_sentence("$GPVTG,89.68,T,,M,0.00,N,0.0,K*5E\r\n").is_none());
+        }
+    }
+
+    mod datetime {
+        use crate::nmea::datetime::{NmeaDate, NmeaDateTime, NmeaTime};
+
+        #[test]
+        fn parse_time() {
+            assert_eq!("131613.000".parse(), Ok(NmeaTime { hour: 13, minute: 16, second: 13, millis: 0 }));
+            assert_eq!("131613.05".parse(), Ok(NmeaTime { hour: 13, minute: 16, second: 13, millis: 50 }));
+            assert_eq!("131613.12345".parse(), Ok(NmeaTime { hour: 13, minute: 16, second: 13, millis: 123 }));
+            assert_eq!("235960".parse(), Ok(NmeaTime { hour: 23, minute: 59, second: 60, millis: 0 }));
+            assert_eq!("19294.00".parse::<NmeaTime>(), Err(()));
+            assert_eq!("136013.000".parse::<NmeaTime>(), Err(()));
+            assert_eq!("131613.0.0".parse::<NmeaTime>(), Err(()));
+            assert_eq!(super::display(NmeaTime::new(1, 2, 3, 4).unwrap()), "01:02:03.004");
+        }
+
+        #[test]
+        fn time_across_boundaries() {
+            let before: NmeaTime = "125959.800".parse().unwrap();
+            let after: NmeaTime = "130000.200".parse().unwrap();
+            // 130000.2 - 125959.8 as floats is 40.4.
+            assert_eq!(after.seconds_since(before), 0.4);
+            let midnight: NmeaTime = "000001.000".parse().unwrap();
+            assert_eq!(midnight.seconds_since("235959.000".parse().unwrap()), 2.0);
+        }
+
+        #[test]
+        fn parse_date() {
+            assert_eq!("110520".parse(), Ok(NmeaDate { year: 2020, month: 5, day: 11 }));
+            assert_eq!("290280".parse(), Ok(NmeaDate { year: 1980, month: 2, day: 29 }));
+            assert_eq!("290221".parse::<NmeaDate>(), Err(()));
+            assert_eq!("11052".parse::<NmeaDate>(), Err(()));
+            assert_eq!(NmeaDate::new(2000, 2, 29).map(super::display).as_deref(), Some("2000-02-29"));
+            assert_eq!(NmeaDate::new(1900, 2, 29), None);
+        }
+
+        #[test]
+        fn unix_millis() {
+            let datetime = NmeaDateTime { date: "110520".parse().unwrap(), time: "131613.500".parse().unwrap() };
+            assert_eq!(datetime.unix_millis(), 1_589_202_973_500);
+            assert_eq!(NmeaDateTime { date: NmeaDate::new(1970, 1, 1).unwrap(), time: NmeaTime::default() }.unix_millis(), 0);
+        }
+
+        #[cfg(feature = "chrono")]
+        #[test]
+        fn to_chrono() {
+            let datetime = NmeaDateTime { date: "110520".parse().unwrap(), time: "131613.500".parse().unwrap() };
+            assert_eq!(datetime.to_chrono().unwrap().timestamp_millis(), datetime.unix_millis());
+        }
+
+        #[cfg(feature = "time")]
+        #[test]
+        fn to_time() {
+            let datetime = NmeaDateTime { date: "110520".parse().unwrap(), time: "131613.500".parse().unwrap() };
+            let time = datetime.to_time().unwrap();
+            assert_eq!((time.unix_timestamp_nanos() / 1_000_000) as i64, datetime.unix_millis());
+        }
+    }
+    mod talker {
+        use crate::nmea::talker::Talker;
+
+        #[test]
+        fn talkers() {
+            assert_eq!(Talker::from_header("$GLGSV"), Talker::Glonass);
+            assert_eq!(Talker::from_header("$BDGSA"), Talker::Beidou);
+            assert_eq!(Talker::from_header("$GNRMC"), Talker::Gnss);
+            assert_eq!(Talker::from_header("$PMTK001"), Talker::Unknown);
+            assert_eq!(Talker::from_header("$"), Talker::Unknown);
+        }
+
+        #[test]
+        fn prefixes() {
+            assert_eq!(Talker::Qzss.prefix(), "QZ");
+            for talker in [Talker::Gps, Talker::Glonass, Talker::Galileo, Talker::Beidou, Talker::Gnss, Talker::Qzss].iter() {
+                let mut header: heapless::String<3> = heapless::String::new();
+                header.push('$').unwrap();
+                header.push_str(talker.prefix()).unwrap();
+                assert_eq!(Talker::from_header(&header), *talker);
+            }
         }
     }
 
     mod gga {
+        use crate::nmea::datetime::NmeaTime;
         use crate::nmea::gga;
+        use crate::nmea::gns::Mode;
+        use crate::nmea::parse_nmea::{Fields, NmeaError};
 
         #[test]
         fn gga_normal() {
             //${GP,GL,GA,GN}GGA, UTC, lat, N/S, long, E/S, Fix quality, Sats used, HDOP, Alt, Alt Units,
             // Geoidal separation, Geo units, Age of diff corr, * checksum
+            let gga = gga::parse_gga(Fields::new("$GPGGA,192940.00,3443.543,N,03443.543,E,1,10,1.01,47.7,M,10.0,M,0.1")).unwrap();
+            // 34 degrees and 43.543 minutes.
+            assert!((gga.lat.unwrap() - 34.725_716_666).abs() < 1e-9);
+            assert!((gga.long.unwrap() - 34.725_716_666).abs() < 1e-9);
             assert_eq!(
-                gga::parse_gga(vec![
-                    "$GPGGA",
-                    "19294.00",
-                    "29343.543",
-                    "N",
-                    "29343.543",
-                    "E",
-                    "1",
-                    "10",
-                    "1.01",
-                    "47.7",
-                    "M",
-                    "10.0",
-                    "M",
-                    "0.1"
-                ]),
+                gga,
                 gga::GgaData {
-                    utc: 19294.00,
-                    lat: Some(34.725716),
-                    long: Some(34.725716),
+                    utc: NmeaTime { hour: 19, minute: 29, second: 40, millis: 0 },
+                    lat: gga.lat,
+                    long: gga.long,
                     sat_fix: gga::SatFix::GpsFix,
                     satellites_used: 10,
                     hdop: Some(1.01),
@@ -609,67 +2152,585 @@ mod nmea_tests {
         }
 
         #[test]
-        #[should_panic]
+        fn gga_fix_quality() {
+            let gga = gga::parse_gga(Fields::new("$GPGGA,131613.000,5132.7314,N,00005.9099,W,6,0,,,M,,M,,")).unwrap();
+            assert_eq!(gga.sat_fix, gga::SatFix::Estimated);
+            assert!(gga.sat_fix.has_fix());
+            assert_eq!(gga.sat_fix.mode(), Mode::Estimated);
+            let gga = gga::parse_gga(Fields::new("$GPGGA,131613.000,5132.7314,N,00005.9099,W,4,12,0.6,42.4,M,47.0,M,1.0,0001")).unwrap();
+            assert_eq!((gga.sat_fix.clone(), gga.sat_fix.quality()), (gga::SatFix::RtkFixed, 4));
+            let gga = gga::parse_gga(Fields::new("$GPGGA,131613.000,,,,,9,0,,,M,,M,,")).unwrap();
+            assert!(!gga.sat_fix.has_fix());
+        }
+
+        #[test]
+        fn fix_quality_and_mode() {
+            for quality in 0..=8 {
+                let sat_fix = gga::SatFix::from_quality(&super::display(quality));
+                assert_eq!(sat_fix.quality(), quality);
+                assert_eq!(gga::SatFix::from_mode(sat_fix.mode()), sat_fix);
+            }
+        }
+
+        #[test]
         fn gga_incorrect_header() {
-            gga::parse_gga(vec![
-                "$GPGSV",
-                "19294.00",
-                "29343.543",
-                "N",
-                "29343.543",
-                "E",
-                "1",
-                "10",
-                "1.01",
-                "47.7",
-                "M",
-                "10.0",
-                "M",
-                "0.1",
-            ]);
+            let gga = gga::parse_gga(Fields::new("$GPGSV,19294.00,29343.543,N,29343.543,E,1,10,1.01,47.7,M,10.0,M,0.1"));
+            assert_eq!(gga, Err(NmeaError::WrongSentence));
+        }
+
+        #[test]
+        fn gga_truncated() {
+            assert_eq!(gga::parse_gga(Fields::new("$GPGGA,131613.000,5132.7314,N")), Err(NmeaError::MissingField(4)));
+            assert_eq!(gga::parse_gga(Fields::new("$GPGGA,,,,,,0,0,,,,,,")), Err(NmeaError::MissingField(1)));
         }
     }
     mod gsa {
         use crate::nmea::gsa;
+        use crate::nmea::parse_nmea::{Fields, NmeaError};
+        use crate::nmea::satellite::{Constellation, SatelliteId};
+        use crate::nmea::talker::Talker;
 
         #[test]
         fn gsa_normal() {
             assert_eq!(
-                gsa::parse_gsa(vec![
-                    "$GPGSA", "M", "2", "01", "02", "03", "04", "05", "06", "07", "08", "09", "10",
-                    "11", "12", "1.0", "2.04", "32.04"
-                ]),
-                gsa::GsaData {
+                gsa::parse_gsa(Fields::new("$GPGSA,M,2,01,02,03,04,05,06,07,08,09,10,11,12,1.0,2.04,32.04")),
+                Ok(gsa::GsaData {
                     mode: gsa::Mode::Manual,
                     dimension_fix: gsa::DimensionFix::Dimension2d,
-                    sat1: Some(1),
-                    sat2: Some(2),
-                    sat3: Some(3),
-                    sat4: Some(4),
-                    sat5: Some(5),
-                    sat6: Some(6),
-                    sat7: Some(7),
-                    sat8: Some(8),
-                    sat9: Some(9),
-                    sat10: Some(10),
-                    sat11: Some(11),
-                    sat12: Some(12),
+                    satellites: (1..=12).map(|prn| SatelliteId { system: Constellation::Gps, prn }).collect(),
                     pdop: Some(1.0),
                     hdop: Some(2.04),
-                    vdop: Some(32.04)
-                }
+                    vdop: Some(32.04),
+                    system: None,
+                })
             )
         }
+
+        #[test]
+        fn gsa_system_id() {
+            let gsa = gsa::parse_gsa(Fields::new("$GNGSA,A,3,65,66,,,,,,,,,,,1.0,0.8,0.6,2")).unwrap();
+            assert_eq!(gsa.system, Some(Talker::Glonass));
+            assert_eq!(gsa.satellites[1], SatelliteId { system: Constellation::Glonass, prn: 66 });
+            assert_eq!(gsa.vdop, Some(0.6));
+            let gsa = gsa::parse_gsa(Fields::new("$GNGSA,A,3,65,66,,,,,,,,,,,1.0,0.8,0.6,"));
+            assert_eq!(gsa.unwrap().system, None);
+            let gsa = gsa::parse_gsa(Fields::new("$GNGSA,A,3,65,66,,,,,,,,,,,1.0,0.8,0.6,x"));
+            assert_eq!(gsa, Err(NmeaError::BadNumber(18)));
+        }
+        #[test]
+        fn gsa_constellations() {
+            let gsa = gsa::parse_gsa(Fields::new("$GNGSA,A,3,05,,48,,67,195,,,,,,,1.0,0.8,0.6")).unwrap();
+            let systems: heapless::Vec<Constellation, 12> = gsa.satellites.iter().map(|satellite| satellite.system).collect();
+            assert_eq!(systems, [Constellation::Gps, Constellation::Sbas, Constellation::Glonass, Constellation::Qzss]);
+            // NMEA 4.10 Galileo numbers its satellites from 1.
+            let gsa = gsa::parse_gsa(Fields::new("$GNGSA,A,3,05,13,,,,,,,,,,,1.0,0.8,0.6,3")).unwrap();
+            assert_eq!(gsa.satellites[0], SatelliteId { system: Constellation::Galileo, prn: 5 });
+            assert_eq!(gsa.satellites.len(), 2);
+        }
+
         #[test]
-        #[should_panic]
         fn gsa_incorrect_header() {
-            gsa::parse_gsa(vec![
-                "$GPGGA", "M", "2", "01", "02", "03", "04", "05", "06", "07", "08", "09", "10",
-                "11", "12", "1.0", "2.04", "32.04",
-            ]);
+            let gsa = gsa::parse_gsa(Fields::new("$GPGGA,M,2,01,02,03,04,05,06,07,08,09,10,11,12,1.0,2.04,32.04"));
+            assert_eq!(gsa, Err(NmeaError::WrongSentence));
+        }
+    }
+    mod gsv {
+        use core::fmt::Write;
+
+        use crate::nmea::gsa;
+        use crate::nmea::gsv;
+        use crate::nmea::parse_nmea::{Fields, NmeaError};
+        use crate::nmea::satellite::{Constellation, SatelliteId};
+        use crate::nmea::talker::Talker;
+
+        #[test]
+        fn gsv_satellites() {
+            let satellites = gsv::parse_gsv(Fields::new("$GPGSV,3,3,10,01,10,45,090,02,,,")).unwrap();
+            assert_eq!(satellites.len(), 2);
+            assert_eq!(satellites[0].snr, Some(90.0));
+            assert_eq!(satellites[1], gsv::Satellites { id: Some(SatelliteId { system: Constellation::Gps, prn: 2 }), ..Default::default() });
+        }
+
+        #[test]
+        fn used_satellites_in_view() {
+            let gsa = gsa::parse_gsa(Fields::new("$GNGSA,A,3,05,13,,,,,,,,,,,1.0,0.8,0.6,3")).unwrap();
+            let satellites = gsv::parse_gsv(Fields::new("$GAGSV,1,1,03,05,40,083,46,13,10,220,,21,63,137,17")).unwrap();
+            let used: heapless::Vec<bool, 4> = satellites.iter()
+                .map(|satellite| gsa.satellites.contains(&satellite.id.unwrap()))
+                .collect();
+            assert_eq!(used, [true, true, false]);
+            assert_eq!(satellites[2].id, Some(SatelliteId { system: Constellation::Galileo, prn: 21 }));
+        }
+
+        #[test]
+        fn gsv_message() {
+            let message = gsv::parse_gsv_message(Fields::new("$GPGSV,3,2,10,01,10,45,090")).unwrap();
+            assert_eq!((message.number_of_messages, message.message_number, message.satellites_in_view), (3, 2, 10));
+            assert_eq!(message.satellites.len(), 1);
+        }
+
+        fn message(sentence: &str) -> gsv::GsvMessage {
+            gsv::parse_gsv_message(Fields::new(sentence)).unwrap()
+        }
+
+        #[test]
+        fn assemble_sets() {
+            let mut assembler = gsv::GsvAssembler::new();
+            assert_eq!(assembler.push(Talker::Gps, message("$GPGSV,2,1,05,01,10,45,090,02,,,,03,,,,04,,,")), Ok(None));
+            assert_eq!(assembler.satellites_in_view(Talker::Gps), Some(5));
+            // Another talker's set in the middle.
+            assert_eq!(assembler.push(Talker::Glonass, message("$GLGSV,2,1,06,65,,,,66,,,,67,,,,68,,,")), Ok(None));
+            let gps = assembler.push(Talker::Gps, message("$GPGSV,2,2,05,05,,,")).unwrap().unwrap();
+            assert_eq!(gps.satellites_in_view, 5);
+            assert_eq!(gps.satellites.len(), 5);
+            assert_eq!(gps.satellites[4].id, Some(SatelliteId { system: Constellation::Gps, prn: 5 }));
+            assert_eq!(assembler.satellites_in_view(Talker::Gps), None);
+            let glonass = assembler.push(Talker::Glonass, message("$GLGSV,2,2,06,69,,,,70,,,")).unwrap().unwrap();
+            assert_eq!(glonass.satellites.len(), 6);
+            // No satellites at all.
+            let empty = assembler.push(Talker::Galileo, message("$GAGSV,1,1,00")).unwrap().unwrap();
+            assert_eq!(empty, gsv::GsvData::default());
+        }
+
+        #[test]
+        fn assemble_out_of_sequence() {
+            let mut assembler = gsv::GsvAssembler::new();
+            // Started half way through a set.
+            assert_eq!(assembler.push(Talker::Gps, message("$GPGSV,3,2,09,05,,,")), Err(NmeaError::GsvSequence));
+            assert_eq!(assembler.push(Talker::Gps, message("$GPGSV,3,1,09,01,,,")), Ok(None));
+            // Skipped message 2.
+            assert_eq!(assembler.push(Talker::Gps, message("$GPGSV,3,3,09,09,,,")), Err(NmeaError::GsvSequence));
+            assert_eq!(assembler.satellites_in_view(Talker::Gps), None);
+            // A new first message starts again.
+            assert_eq!(assembler.push(Talker::Gps, message("$GPGSV,2,1,05,01,,,")), Ok(None));
+            assert_eq!(assembler.push(Talker::Gps, message("$GPGSV,2,1,05,01,,,")), Ok(None));
+            // Wrong total.
+            assert_eq!(assembler.push(Talker::Gps, message("$GPGSV,3,2,05,05,,,")), Err(NmeaError::GsvSequence));
+            assert_eq!(assembler.push(Talker::Gps, message("$GPGSV,0,0,00")), Err(NmeaError::GsvSequence));
+        }
+
+        #[test]
+        fn assemble_largest_set() {
+            // GPS and BeiDou together, 9 messages of 4.
+            let mut assembler = gsv::GsvAssembler::new();
+            for message_number in 1..=9 {
+                let mut sentence: heapless::String<128> = heapless::String::new();
+                write!(sentence, "$GNGSV,9,{},36", message_number).unwrap();
+                for satellite in 0..4 {
+                    write!(sentence, ",{},10,45,30", (message_number - 1) * 4 + satellite + 1).unwrap();
+                }
+                let set = assembler.push(Talker::Gnss, message(&sentence)).unwrap();
+                assert_eq!(set.is_some(), message_number == 9);
+                if let Some(set) = set {
+                    assert_eq!(set.satellites.len(), 36);
+                    assert_eq!(set.satellites[35].id.map(|id| id.prn), Some(36));
+                }
+            }
+            // More messages than NMEA allows can't be put together.
+            assert_eq!(assembler.push(Talker::Gnss, message("$GNGSV,10,1,40,01,,,")), Err(NmeaError::GsvSequence));
+            assert_eq!(assembler.push(Talker::Gnss, message("$GNGSV,9,1,36,01,,,")), Ok(None));
+            assert_eq!(assembler.push(Talker::Gnss, message("$GNGSV,10,2,40,01,,,")), Err(NmeaError::GsvSequence));
+            assert_eq!(assembler.satellites_in_view(Talker::Gnss), None);
+        }
+
+        #[test]
+        fn gsv_signal_id() {
+            let signal = message("$GPGSV,3,3,10,01,10,45,090,02,,,,1");
+            assert_eq!(signal.satellites.len(), 2);
+            assert_eq!(signal.signal_id, Some(1));
+            assert_eq!(message("$GPGSV,1,1,00,8").signal_id, Some(8));
+            assert_eq!(message("$GPGSV,3,3,10,01,10,45,090,02,,,").signal_id, None);
+
+            // The L1 and L5 sets mixed together.
+            let mut assembler = gsv::GsvAssembler::new();
+            assert_eq!(assembler.push(Talker::Gps, message("$GPGSV,2,1,05,01,,,,02,,,,03,,,,04,,,,1")), Ok(None));
+            let l5 = assembler.push(Talker::Gps, message("$GPGSV,1,1,01,01,,,,8")).unwrap().unwrap();
+            assert_eq!((l5.satellites.len(), l5.signal_id), (1, Some(8)));
+            let l1 = assembler.push(Talker::Gps, message("$GPGSV,2,2,05,05,,,,1")).unwrap().unwrap();
+            assert_eq!((l1.satellites.len(), l1.signal_id), (5, Some(1)));
+        }
+
+        #[test]
+        fn gsv_bad_snr() {
+            assert_eq!(gsv::parse_gsv(Fields::new("$GPGSV,1,1,01,10,45,090,4x")), Err(NmeaError::BadNumber(7)));
+        }
+    }
+    mod rmc {
+        use crate::nmea::datetime::NmeaDate;
+        use crate::nmea::gga::SatFix;
+        use crate::nmea::gns::{Mode, NavStatus};
+        use crate::nmea::rmc;
+        use crate::nmea::parse_nmea::{Fields, NmeaError};
+
+        #[test]
+        fn rmc_west_variation() {
+            let rmc = rmc::parse_rmc(Fields::new("$GPRMC,131613.000,A,,,,,0.00,89.68,160920,1.5,W,A"));
+            assert_eq!(rmc.unwrap().mag_var, Some(-1.5));
+            let rmc = rmc::parse_rmc(Fields::new("$GPRMC,131613.000,A,,,,,0.00,89.68,160920,,W,A"));
+            assert_eq!(rmc.unwrap().mag_var, None);
+            let rmc = rmc::parse_rmc(Fields::new("$GPRMC,131613.000,A,,,,,0.00,89.68,160920"));
+            assert_eq!(rmc.unwrap().mag_var, None);
+        }
+
+        #[test]
+        fn rmc_nav_status() {
+            let rmc = rmc::parse_rmc(Fields::new("$GNRMC,131613.000,A,5132.7314,N,00005.9099,W,0.17,89.68,160920,,,A,V")).unwrap();
+            assert_eq!(rmc.nav_status, Some(NavStatus::NotValid));
+            assert_eq!(rmc.longitude, Some(-(5.9099 / 60.0)));
+            let rmc = rmc::parse_rmc(Fields::new("$GNRMC,131613.000,A,5132.7314,N,00005.9099,W,0.17,89.68,160920,,,A"));
+            assert_eq!(rmc.unwrap().nav_status, None);
+        }
+
+        #[test]
+        fn rmc_mode() {
+            let rmc = rmc::parse_rmc(Fields::new("$GNRMC,131613.000,A,5132.7314,N,00005.9099,W,0.17,89.68,160920,,,E,C")).unwrap();
+            assert_eq!((rmc.mode, rmc.nav_status), (Some(Mode::Estimated), Some(NavStatus::Caution)));
+            assert_eq!(SatFix::from_mode(rmc.mode.unwrap()), SatFix::Estimated);
+            let rmc = rmc::parse_rmc(Fields::new("$GPRMC,131613.000,A,,,,,0.00,89.68,160920,1.5,W"));
+            assert_eq!(rmc.unwrap().mode, None);
+        }
+
+        #[test]
+        fn rmc_datetime() {
+            let rmc = rmc::parse_rmc(Fields::new("$GPRMC,235959.500,A,,,,,0.00,89.68,311299,,,A")).unwrap();
+            assert_eq!(rmc.date, Some(NmeaDate { year: 1999, month: 12, day: 31 }));
+            let end_of_1999 = rmc.datetime().unwrap();
+            let rmc = rmc::parse_rmc(Fields::new("$GPRMC,000000.250,A,,,,,0.00,89.68,010100,,,A")).unwrap();
+            assert_eq!(rmc.datetime().unwrap().seconds_since(end_of_1999), 0.75);
+            let rmc = rmc::parse_rmc(Fields::new("$GPRMC,,V,,,,,,,,,,N")).unwrap();
+            assert_eq!((rmc.utc, rmc.date, rmc.datetime()), (None, None, None));
+            let rmc = rmc::parse_rmc(Fields::new("$GPRMC,131613.000,A,,,,,0.00,89.68,300220,,,A"));
+            assert_eq!(rmc, Err(NmeaError::BadNumber(9)));
+        }
+
+        #[test]
+        fn rmc_bad_direction() {
+            let rmc = rmc::parse_rmc(Fields::new("$GPRMC,131613.000,A,5132.7314,Q,,,,,160920"));
+            assert_eq!(rmc, Err(NmeaError::BadDirection(4)));
+        }
+    }
+    mod gns {
+        use crate::GpsSentence;
+        use crate::nmea::datetime::NmeaTime;
+        use crate::nmea::gns::{self, Mode, NavStatus};
+        use crate::nmea::parse_nmea::{Fields, NmeaError};
+        use crate::nmea::talker::Talker;
+
+        #[test]
+        fn gns_normal() {
+            let gns = gns::parse_gns(Fields::new("$GNGNS,014035.00,4332.69262,S,17235.48549,E,RR,13,0.9,25.63,11.24,,")).unwrap();
+            assert_eq!(gns.utc, NmeaTime { hour: 1, minute: 40, second: 35, millis: 0 });
+            assert_eq!(gns.lat, Some(-(43.0 + 32.69262 / 60.0)));
+            assert_eq!(gns.long, Some(172.0 + 35.48549 / 60.0));
+            assert_eq!(gns.modes.as_slice(), &[Mode::RtkFixed, Mode::RtkFixed]);
+            assert_eq!(gns.mode(Talker::Glonass), Some(Mode::RtkFixed));
+            assert_eq!(gns.mode(Talker::Galileo), None);
+            assert_eq!(gns.satellites_used, 13);
+            assert_eq!((gns.hdop, gns.msl_alt, gns.geoidal_sep), (Some(0.9), Some(25.63), Some(11.24)));
+            assert_eq!((gns.age_diff_corr, gns.diff_station_id, gns.nav_status), (None, None, None));
+            assert_eq!(gns::parse_gns(Fields::new("$GNGNS,014035.00,4332.69262,S,17235.48549,E,RR,13")), Err(NmeaError::MissingField(8)));
+        }
+
+        #[test]
+        fn gns_sentence() {
+            match GpsSentence::from_sentence("$GNGNS,131613.000,5132.7314,N,00005.9099,W,AAN,09,1.17,42.4,47.0,,,S*4B") {
+                GpsSentence::GNS(talker, gns) => {
+                    assert_eq!(talker, Talker::Gnss);
+                    assert_eq!(gns.mode(Talker::Galileo), Some(Mode::NoFix));
+                    assert_eq!(gns.nav_status, Some(NavStatus::Safe));
+                }
+                other => panic!("Expected GNS, got {:?}", other),
+            }
+        }
+    }
+    mod gst {
+        use crate::nmea::datetime::NmeaTime;
+        use crate::nmea::gst;
+        use crate::nmea::parse_nmea::{Fields, NmeaError};
+
+        #[test]
+        fn gst_normal() {
+            assert_eq!(
+                gst::parse_gst(Fields::new("$GPGST,172814.0,0.006,0.023,0.020,273.6,0.023,0.020,0.031")),
+                Ok(gst::GstData {
+                    utc: NmeaTime { hour: 17, minute: 28, second: 14, millis: 0 },
+                    rms: Some(0.006),
+                    semi_major: Some(0.023),
+                    semi_minor: Some(0.020),
+                    orientation: Some(273.6),
+                    lat_std_dev: Some(0.023),
+                    long_std_dev: Some(0.020),
+                    alt_std_dev: Some(0.031),
+                })
+            );
+            let gst = gst::parse_gst(Fields::new("$GPGST,172814.0,,,,,,,")).unwrap();
+            assert_eq!(gst.lat_std_dev, None);
+            assert_eq!(gst::parse_gst(Fields::new("$GPGST,172814.0,0.006,0.023,0.020,273.6,0.023")), Err(NmeaError::MissingField(7)));
+        }
+    }
+    mod zda {
+        use crate::nmea::datetime::{NmeaDate, NmeaTime};
+        use crate::nmea::zda;
+        use crate::nmea::parse_nmea::{Fields, NmeaError};
+
+        #[test]
+        fn zda_normal() {
+            assert_eq!(
+                zda::parse_zda(Fields::new("$GPZDA,131613.000,11,05,2020,-01,30")),
+                Ok(zda::ZdaData {
+                    utc: NmeaTime { hour: 13, minute: 16, second: 13, millis: 0 },
+                    date: Some(NmeaDate { year: 2020, month: 5, day: 11 }),
+                    local_zone_hours: Some(-1),
+                    local_zone_minutes: Some(30),
+                })
+            );
+            let zda = zda::parse_zda(Fields::new("$GPZDA,131613.000,11,05,2020,,")).unwrap();
+            assert_eq!(zda.local_zone_hours, None);
+            assert_eq!(super::display(zda.datetime().unwrap()), "2020-05-11T13:16:13.000Z");
+            let zda = zda::parse_zda(Fields::new("$GPZDA,131613.000,,,,,")).unwrap();
+            assert_eq!(zda.datetime(), None);
+            assert_eq!(zda::parse_zda(Fields::new("$GPZDA,131613.000,31,04,2020,,")), Err(NmeaError::BadNumber(2)));
+            assert_eq!(zda::parse_zda(Fields::new("$GPZDA,131613.000,11,05")), Err(NmeaError::MissingField(4)));
+        }
+    }
+    mod vtg {
+        use crate::nmea::gns::Mode;
+        use crate::nmea::vtg;
+        use crate::nmea::parse_nmea::Fields;
+
+        #[test]
+        fn vtg_mode() {
+            let vtg = vtg::parse_vtg(Fields::new("$GPVTG,89.68,T,,M,0.00,N,0.0,K,D")).unwrap();
+            assert_eq!(vtg.true_course, Some(89.68));
+            assert_eq!(vtg.magnetic_course, None);
+            assert_eq!(vtg.mode, Some(Mode::Differential));
+            assert_eq!(vtg::parse_vtg(Fields::new("$GPVTG,89.68,T,,M,0.00,N,0.0,K,R")).unwrap().mode, Some(Mode::RtkFixed));
+            assert_eq!(vtg::parse_vtg(Fields::new("$GPVTG,89.68,T,,M,0.00,N,0.0,K,N")).unwrap().mode, Some(Mode::NoFix));
+            assert_eq!(vtg::parse_vtg(Fields::new("$GPVTG,89.68,T,,M,0.00,N,0.0,K")).unwrap().mode, None);
+        }
+    }
+
+    mod write_nmea {
+        use heapless::Vec;
+        use proptest::prelude::*;
+
+        use crate::nmea::datetime::{NmeaDate, NmeaTime};
+        use crate::nmea::gga::{parse_gga, GgaData, SatFix};
+        use crate::nmea::gll::{parse_gll, GllData};
+        use crate::nmea::gns::{parse_gns, GnsData, Mode, NavStatus};
+        use crate::nmea::gsa::{self, parse_gsa, GsaData};
+        use crate::nmea::gst::{parse_gst, GstData};
+        use crate::nmea::gsv::{parse_gsv_message, GsvAssembler, GsvData, Satellites, MAX_SATELLITES};
+        use crate::nmea::parse_nmea::parse_sentence;
+        use crate::nmea::rmc::{parse_rmc, RmcData};
+        use crate::nmea::satellite::SatelliteId;
+        use crate::nmea::talker::Talker;
+        use crate::nmea::vtg::{parse_vtg, VtgData};
+        use crate::nmea::zda::{parse_zda, ZdaData};
+
+        #[test]
+        fn gga_sentence() {
+            let sentence = "$GPGGA,131613.000,5132.7314,N,00005.9099,W,1,9,1.17,42.4,M,47.0,M,,*7E";
+            let gga = parse_gga(parse_sentence(sentence).unwrap()).unwrap();
+            assert_eq!(gga.to_sentence(Talker::Gps),
+                       "$GPGGA,131613.000,5132.731400,N,00005.909900,W,1,9,1.17,42.4,M,47,M,,*60\r\n");
+        }
+
+        #[test]
+        fn rmc_sentence() {
+            let rmc = RmcData {
+                utc: NmeaTime::new(13, 16, 13, 0),
+                fix_status: true,
+                latitude: Some(51.0 + 32.7314 / 60.0),
+                longitude: Some(-5.9099 / 60.0),
+                speed: Some(0.17),
+                course: Some(0.0),
+                date: NmeaDate::new(2020, 5, 11),
+                mag_var: Some(-3.5),
+                mode: Some(Mode::Autonomous),
+                nav_status: Some(NavStatus::NotValid),
+            };
+            assert_eq!(rmc.to_sentence(Talker::Gnss),
+                       "$GNRMC,131613.000,A,5132.731400,N,00005.909900,W,0.17,0,110520,3.5,W,A,V*47\r\n");
+        }
+
+        #[test]
+        fn gsv_sentences() {
+            let mut satellites = Vec::new();
+            let id = |prn| Some(SatelliteId::from_prn(Talker::Gps, prn));
+            let _ = satellites.push(Satellites { id: id(7), ..Default::default() });
+            let _ = satellites.push(Satellites { id: id(10), elevation: Some(63.0), azimuth: Some(137.0), snr: Some(17.0) });
+            let gsv = GsvData { satellites_in_view: 5, satellites, signal_id: None };
+            assert_eq!(gsv.to_sentences(Talker::Gps), ["$GPGSV,1,1,5,7,,,,10,63,137,17*4C\r\n"]);
+            let empty = GsvData { signal_id: Some(1), ..Default::default() };
+            assert_eq!(empty.to_sentences(Talker::Glonass), ["$GLGSV,1,1,0,1*48\r\n"]);
+        }
+
+        #[test]
+        fn minutes_carry_into_degrees() {
+            let gll = GllData { latitude: Some(-(1.0 - 1e-9)), longitude: Some(179.999_999_999), ..Default::default() };
+            assert!(gll.to_sentence(Talker::Gps).starts_with("$GPGLL,0100.000000,S,18000.000000,E,,V,*"));
+        }
+
+        fn time() -> impl Strategy<Value = NmeaTime> {
+            (0u8..24, 0u8..60, 0u8..60, 0u16..1000)
+                .prop_map(|(hour, minute, second, millis)| NmeaTime { hour, minute, second, millis })
+        }
+
+        /// Years that ddmmyy can hold.
+        fn date() -> impl Strategy<Value = NmeaDate> {
+            (1980u16..2080, 1u8..13, 1u8..29).prop_map(|(year, month, day)| NmeaDate { year, month, day })
+        }
+
+        fn number() -> impl Strategy<Value = Option<f32>> {
+            prop::option::of(-10_000.0f32..10_000.0)
+        }
+
+        fn lat() -> impl Strategy<Value = Option<f64>> {
+            prop::option::of(-90.0f64..90.0)
+        }
+
+        fn long() -> impl Strategy<Value = Option<f64>> {
+            prop::option::of(-180.0f64..180.0)
+        }
+
+        /// Every mode but Unknown, that is written as N.
+        fn mode() -> impl Strategy<Value = Mode> {
+            prop_oneof![
+                Just(Mode::NoFix), Just(Mode::Autonomous), Just(Mode::Differential), Just(Mode::Precise), Just(Mode::RtkFixed),
+                Just(Mode::RtkFloat), Just(Mode::Estimated), Just(Mode::Manual), Just(Mode::Simulator),
+            ]
+        }
+
+        fn nav_status() -> impl Strategy<Value = Option<NavStatus>> {
+            prop::option::of(prop_oneof![
+                Just(NavStatus::Safe), Just(NavStatus::Caution), Just(NavStatus::Unsafe), Just(NavStatus::NotValid),
+            ])
+        }
+
+        /// Latitude and longitude are written to 6 decimal places of a minute.
+        fn assert_degrees(parsed: Option<f64>, written: Option<f64>) {
+            match (parsed, written) {
+                (Some(parsed), Some(written)) => assert!((parsed - written).abs() < 1e-8, "{} != {}", parsed, written),
+                _ => assert_eq!(parsed, written),
+            }
+        }
+
+        proptest! {
+            #[test]
+            fn gga_round_trip(utc in time(), lat in lat(), long in long(), mode in mode(), satellites_used in 0..40,
+                              hdop in number(), msl_alt in number(), geoidal_sep in number(), age_diff_corr in number()) {
+                let sat_fix = SatFix::from_mode(mode);
+                let gga = GgaData { utc, lat, long, sat_fix, satellites_used, hdop, msl_alt, geoidal_sep, age_diff_corr };
+                let sentence = gga.to_sentence(Talker::Gps);
+                let mut parsed = parse_gga(parse_sentence(&sentence).unwrap()).unwrap();
+                assert_degrees(parsed.lat, gga.lat);
+                assert_degrees(parsed.long, gga.long);
+                prop_assert_eq!(parsed.to_sentence(Talker::Gps), sentence);
+                parsed.lat = gga.lat;
+                parsed.long = gga.long;
+                prop_assert_eq!(parsed, gga);
+            }
+
+            #[test]
+            fn rmc_round_trip(utc in prop::option::of(time()), fix_status: bool, latitude in lat(), longitude in long(),
+                              speed in number(), course in number(), date in prop::option::of(date()),
+                              mag_var in number(), mode in prop::option::of(mode()), nav_status in nav_status()) {
+                let rmc = RmcData { utc, fix_status, latitude, longitude, speed, course, date, mag_var, mode, nav_status };
+                let sentence = rmc.to_sentence(Talker::Gnss);
+                let mut parsed = parse_rmc(parse_sentence(&sentence).unwrap()).unwrap();
+                assert_degrees(parsed.latitude, rmc.latitude);
+                assert_degrees(parsed.longitude, rmc.longitude);
+                parsed.latitude = rmc.latitude;
+                parsed.longitude = rmc.longitude;
+                prop_assert_eq!(parsed, rmc);
+            }
+
+            #[test]
+            fn vtg_round_trip(true_course in number(), magnetic_course in number(), speed_knots in number(),
+                              speed_kph in number(), mode in prop::option::of(mode())) {
+                let vtg = VtgData { true_course, magnetic_course, speed_knots, speed_kph, mode };
+                let sentence = vtg.to_sentence(Talker::Gps);
+                prop_assert_eq!(parse_vtg(parse_sentence(&sentence).unwrap()).unwrap(), vtg);
+            }
+
+            #[test]
+            fn gsa_round_trip(automatic: bool, fix in 0..3, prns in prop::collection::vec(1u16..300, 0..=12),
+                              pdop in number(), hdop in number(), vdop in number(), system in prop::option::of(1..6)) {
+                let system = system.map(Talker::from_system_id);
+                let gsa = GsaData {
+                    mode: if automatic { gsa::Mode::Automatic } else { gsa::Mode::Manual },
+                    dimension_fix: [gsa::DimensionFix::NotAvailable, gsa::DimensionFix::Dimension2d, gsa::DimensionFix::Dimension3d][fix as usize].clone(),
+                    satellites: prns.into_iter().map(|prn| SatelliteId::from_prn(system.unwrap_or(Talker::Gnss), prn)).collect(),
+                    pdop, hdop, vdop, system,
+                };
+                let sentence = gsa.to_sentence(Talker::Gnss);
+                prop_assert_eq!(parse_gsa(parse_sentence(&sentence).unwrap()).unwrap(), gsa);
+            }
+
+            #[test]
+            fn gll_round_trip(latitude in lat(), longitude in long(), utc in prop::option::of(time()), is_valid: bool,
+                              mode in prop::option::of(mode())) {
+                let gll = GllData { latitude, longitude, utc, is_valid, mode };
+                let sentence = gll.to_sentence(Talker::Gps);
+                let mut parsed = parse_gll(parse_sentence(&sentence).unwrap()).unwrap();
+                assert_degrees(parsed.latitude, gll.latitude);
+                assert_degrees(parsed.longitude, gll.longitude);
+                parsed.latitude = gll.latitude;
+                parsed.longitude = gll.longitude;
+                prop_assert_eq!(parsed, gll);
+            }
+
+            #[test]
+            fn gsv_round_trip(satellites_in_view in 0..40,
+                              satellites in prop::collection::vec((prop::option::of(1u16..300), number(), number(), number()), 0..=MAX_SATELLITES),
+                              signal_id in prop::option::of(1..12)) {
+                let satellites = satellites.into_iter()
+                    .map(|(prn, elevation, azimuth, snr)| Satellites {
+                        id: prn.map(|prn| SatelliteId::from_prn(Talker::Galileo, prn)), elevation, azimuth, snr,
+                    })
+                    .collect();
+                let gsv = GsvData { satellites_in_view, satellites, signal_id };
+                let mut assembler = GsvAssembler::new();
+                let mut parsed = None;
+                for sentence in gsv.to_sentences(Talker::Galileo).iter() {
+                    let fields = parse_sentence(sentence).unwrap();
+                    prop_assert_eq!(fields.talker(), Talker::Galileo);
+                    parsed = assembler.push(fields.talker(), parse_gsv_message(fields).unwrap()).unwrap();
+                }
+                prop_assert_eq!(parsed, Some(gsv));
+            }
+
+            #[test]
+            fn gns_round_trip(utc in time(), lat in lat(), long in long(), modes in prop::collection::vec(mode(), 0..=6),
+                              satellites_used in 0..40, hdop in number(), msl_alt in number(), geoidal_sep in number(),
+                              age_diff_corr in number(), diff_station_id in prop::option::of(0..1024), nav_status in nav_status()) {
+                let modes = modes.into_iter().collect();
+                let gns = GnsData { utc, lat, long, modes, satellites_used, hdop, msl_alt, geoidal_sep, age_diff_corr, diff_station_id, nav_status };
+                let sentence = gns.to_sentence(Talker::Gnss);
+                let mut parsed = parse_gns(parse_sentence(&sentence).unwrap()).unwrap();
+                assert_degrees(parsed.lat, gns.lat);
+                assert_degrees(parsed.long, gns.long);
+                parsed.lat = gns.lat;
+                parsed.long = gns.long;
+                prop_assert_eq!(parsed, gns);
+            }
+
+            #[test]
+            fn zda_round_trip(utc in time(), date in prop::option::of(date()), local_zone_hours in prop::option::of(-13..=13),
+                              local_zone_minutes in prop::option::of(0..60)) {
+                let zda = ZdaData { utc, date, local_zone_hours, local_zone_minutes };
+                let sentence = zda.to_sentence(Talker::Gps);
+                prop_assert_eq!(parse_zda(parse_sentence(&sentence).unwrap()).unwrap(), zda);
+            }
+
+            #[test]
+            fn gst_round_trip(utc in time(), rms in number(), semi_major in number(), semi_minor in number(),
+                              orientation in number(), lat_std_dev in number(), long_std_dev in number(), alt_std_dev in number()) {
+                let gst = GstData { utc, rms, semi_major, semi_minor, orientation, lat_std_dev, long_std_dev, alt_std_dev };
+                let sentence = gst.to_sentence(Talker::Gps);
+                prop_assert_eq!(parse_gst(parse_sentence(&sentence).unwrap()).unwrap(), gst);
+            }
         }
     }
-    mod gsv {}
-    mod rmc {}
-    mod vtg {}
 }
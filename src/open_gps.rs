@@ -6,104 +6,404 @@
 pub mod gps {
     //! This is the main module around which all other modules interact.
     //! It contains the Gps structure, open port and GpsData that are central to using this module.
+    use core::str;
+    #[cfg(feature = "std")]
     use std::fs::{File, OpenOptions};
-    use std::io::{Read, Write};
-    use std::str;
-    use std::time::{Duration, SystemTime};
+    #[cfg(feature = "std")]
+    use std::io::{self, Read, Write};
+    use core::time::Duration;
+    #[cfg(feature = "std")]
+    use std::time::SystemTime;
 
+    #[cfg(feature = "std")]
     use bincode::serialize;
+    use heapless::{String, Vec};
     use serde::{Deserialize, Serialize};
+    #[cfg(feature = "std")]
     use serialport::prelude::*;
 
+    #[cfg(feature = "std")]
+    use crate::error::Error;
     use crate::nmea::gga::{GgaData, parse_gga};
     use crate::nmea::gll::{GllData, parse_gll};
+    use crate::nmea::gns::{GnsData, parse_gns};
+    use crate::nmea::gst::{GstData, parse_gst};
     use crate::nmea::gsa::{GsaData, parse_gsa};
-    use crate::nmea::gsv::{parse_gsv, Satellites};
-    use crate::nmea::parse_nmea::parse_sentence;
+    use crate::nmea::gsv::{GsvAssembler, GsvData, MAX_MESSAGES, parse_gsv_message};
+    use crate::nmea::parse_nmea::{Fields, NmeaError, parse_sentence};
     use crate::nmea::rmc::{parse_rmc, RmcData};
+    use crate::nmea::talker::Talker;
+    use crate::nmea::zda::{parse_zda, ZdaData};
     use crate::nmea::vtg::{parse_vtg, VtgData};
+    use crate::pmtk::send_pmtk::{add_checksum, format_command, parse_pmtk, Pmtk001Ack, SystemMessage};
+
+    /// Longest sentence read from the gps, in bytes. A line longer than this is cut off.
+    pub const MAX_SENTENCE_LENGTH: usize = 256;
+
+    /// Longest header of a GpsSentence::Unknown, without the $. A sentence ID is 5 (GPTXT) or a
+    /// few more for proprietary sentences.
+    pub const MAX_HEADER_LENGTH: usize = 8;
+
+    /// How long and how much Gps reads before giving up on a line.
+    ///
+    /// - port_timeout -> Time out of the serial port opened by Gps::new_with_options, for each read.
+    /// - no_data_timeout -> How long read_line waits for a full line before giving NoConnection.
+    ///   update, pmtk_001, pmtk_500 and pmtk_startup all read with read_line, so they all use it.
+    ///   Without std there is no clock, and only the port can time out.
+    /// - max_sentence_length -> A line is cut off after this many bytes. At most MAX_SENTENCE_LENGTH.
+    ///
+    /// The defaults (1 second, 1 second, 256 bytes) suit a 1Hz fix at 9600 baud. Shorten the
+    /// timeouts at 10Hz and 115200 baud, lengthen them for periodic or AlwaysLocate modes where the
+    /// gps is quiet for a long time.
+    /// ```ignore
+    /// let options = GpsOptions::default()
+    ///     .port_timeout(Duration::from_millis(100))
+    ///     .no_data_timeout(Duration::from_millis(200));
+    /// let mut gps = Gps::new_with_options("/dev/serial0", "115200", options)?;
+    /// ```
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    pub struct GpsOptions {
+        pub port_timeout: Duration,
+        pub no_data_timeout: Duration,
+        pub max_sentence_length: usize,
+    }
+
+    impl Default for GpsOptions {
+        fn default() -> GpsOptions {
+            GpsOptions {
+                port_timeout: Duration::from_millis(1000),
+                no_data_timeout: Duration::from_secs(1),
+                max_sentence_length: MAX_SENTENCE_LENGTH,
+            }
+        }
+    }
+
+    impl GpsOptions {
+        pub fn port_timeout(mut self, timeout: Duration) -> GpsOptions {
+            self.port_timeout = timeout;
+            self
+        }
+
+        pub fn no_data_timeout(mut self, timeout: Duration) -> GpsOptions {
+            self.no_data_timeout = timeout;
+            self
+        }
+
+        /// Longer than MAX_SENTENCE_LENGTH is cut down to it.
+        pub fn max_sentence_length(mut self, length: usize) -> GpsOptions {
+            self.max_sentence_length = length.min(MAX_SENTENCE_LENGTH);
+            self
+        }
+    }
 
     /// Opens the port to the GPS, probably /dev/serial0
         /// Default baud rate is 9600
-    pub fn open_port(port_name: &str, baud_rate: u32) -> Box<dyn SerialPort> {
+    #[cfg(feature = "std")]
+    pub fn open_port(port_name: &str, baud_rate: u32) -> Result<Box<dyn SerialPort>, Error> {
+        open_port_with_timeout(port_name, baud_rate, GpsOptions::default().port_timeout)
+    }
+
+    /// Opens the port to the GPS with the time out for each read.
+    #[cfg(feature = "std")]
+    pub fn open_port_with_timeout(port_name: &str, baud_rate: u32, timeout: Duration) -> Result<Box<dyn SerialPort>, Error> {
         let settings = SerialPortSettings {
             baud_rate,
             data_bits: DataBits::Eight,
             flow_control: FlowControl::None,
             parity: Parity::None,
             stop_bits: StopBits::One,
-            timeout: Duration::from_millis(1000),
+            timeout,
         };
-        match serialport::open_with_settings(port_name, &settings) {
-            Ok(port) => return port,
-            Err(_e) => panic!("Port not found: {} - {}", port_name, _e),
-        }
+        Ok(serialport::open_with_settings(port_name, &settings)?)
     }
 
     /// Checks if a sentence is a valid sentence by checksumming the sentence and comparing it to
     /// the given checksum. Returns true for valid sentence, false for invalid.
     /// The format of the sentence should be $sentence*checksum
     pub fn is_valid_checksum(s: &str) -> bool {
-        let s = s.trim();
-        // String should be: $..., *XY
+        // String should be: $..., *XY. Checked as bytes, a line of noise may not split into chars.
+        let s = s.trim().as_bytes();
+        if s.len() < 4 || s[0] != b'$' || s[s.len() - 3] != b'*' {
+            return false;
+        }
+        let (body, checksum) = (&s[1..s.len() - 3], &s[s.len() - 2..]);
+        if !checksum.iter().all(|digit| digit.is_ascii_hexdigit()) {
+            return false;
+        }
+        // Only hex digits, so it's a str.
+        match u8::from_str_radix(str::from_utf8(checksum).unwrap_or(""), 16) {
+            // bitwise xor for each byte in the body, without the $.
+            Ok(expected_checksum) => body.iter().fold(0, |actual, byte| actual ^ byte) == expected_checksum,
+            Err(_e) => false,
+        }
+    }
 
-        let star = &s[s.len() - 3..s.len() - 2];
-        let checksum = &s[s.len() - 2..s.len()];
-        let body = &s[0..s.len() - 3];
+    /// Why reading from or writing to the port failed.
+    /// - TimedOut -> No bytes arrived before the port timed out.
+    /// - Other -> Anything else went wrong with the port.
+    #[derive(PartialEq, Debug, Clone, Copy)]
+    pub enum PortError {
+        TimedOut,
+        Other,
+    }
 
-        if star != "*" {
-            // Check third last item is a *
-            return false;
+    /// The byte transport between Gps and the gps module.
+    ///
+    /// With the std feature this is implemented for everything that is Read + Write. Without std,
+    /// implement it for your uart. As there is no clock without std, read_bytes must return
+    /// PortError::TimedOut when no bytes arrive in a reasonable time so the Gps can report
+    /// NoConnection.
+    pub trait GpsPort {
+        /// Read bytes into buf and return how many were read. Ok(0) is nothing has arrived yet.
+        fn read_bytes(&mut self, buf: &mut [u8]) -> Result<usize, PortError>;
+
+        /// Write all the bytes to the gps.
+        fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), PortError>;
+    }
+
+    #[cfg(feature = "std")]
+    impl<T: Read + Write> GpsPort for T {
+        fn read_bytes(&mut self, buf: &mut [u8]) -> Result<usize, PortError> {
+            self.read(buf).map_err(|e| match e.kind() {
+                std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock => PortError::TimedOut,
+                _ => PortError::Other,
+            })
         }
 
-        match u8::from_str_radix(checksum, 16) {
-            // Convert to base 16.
-            Ok(expected_checksum) => {
-                let mut actual: u8 = 0;
-                for i in body[1..].as_bytes() {
-                    // Skip $ sign. bitwise xor for each i in body
-                    actual ^= *i;
-                }
-                return actual == expected_checksum;
-            }
-            Err(_e) => return false,
+        fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), PortError> {
+            self.write_all(bytes)
+                .and_then(|_| self.flush())
+                .map_err(|_e| PortError::Other)
         }
     }
 
     /// Enum for if the port connection to the gps is valid, gave invalid bytes, or is not connected
     #[derive(PartialEq, Debug)]
     pub enum PortConnection {
-        Valid(String),
-        InvalidBytes(Vec<u8>),
+        Valid(String<MAX_SENTENCE_LENGTH>),
+        InvalidBytes(Vec<u8, MAX_SENTENCE_LENGTH>),
         NoConnection,
     }
 
+    /// A full GSV set in a GpsSentence. With std it is boxed, so every GpsSentence isn't the size of
+    /// the largest set (about 1 KB). Without an allocator it is held in place.
+    #[cfg(feature = "std")]
+    pub type GsvSet = Box<GsvData>;
+    #[cfg(not(feature = "std"))]
+    pub type GsvSet = GsvData;
+
     /// Enum for the gps.update() method.
     #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+    #[cfg_attr(not(feature = "std"), allow(clippy::large_enum_variant))]
+    ///
+    /// Each sentence comes with the Talker that gave it, eg GSA(Talker::Glonass, ..) for $GLGSA.
     pub enum GpsSentence {
-        GGA(GgaData),
-        VTG(VtgData),
-        GSA(GsaData),
-        GSV(Vec<Satellites>),
-        GLL(GllData),
-        RMC(RmcData),
+        GGA(Talker, GgaData),
+        VTG(Talker, VtgData),
+        GSA(Talker, GsaData),
+        GSV(Talker, GsvSet),
+        GLL(Talker, GllData),
+        RMC(Talker, RmcData),
+        ZDA(Talker, ZdaData),
+        GNS(Talker, GnsData),
+        GST(Talker, GstData),
+        /// Ack for a command: the command's packet type and the ack flag.
+        PMTK001(u16, Pmtk001Ack),
+        PMTK010(SystemMessage),
+        /// Text message, "MTKGPS" on startup.
+        PMTK011(String<MAX_SENTENCE_LENGTH>),
+        /// Any other PMTK reply: its packet type and data fields without the checksum.
+        PMTK(u16, String<MAX_SENTENCE_LENGTH>),
+        /// A sentence with a valid checksum that isn't parsed, eg $PGTOP or $GPTXT. header is
+        /// without the $, fields are the rest of the sentence without the checksum. A header longer
+        /// than MAX_HEADER_LENGTH isn't a sentence ID, it gives ParseError(WrongSentence) instead.
+        Unknown { header: String<MAX_HEADER_LENGTH>, fields: String<MAX_SENTENCE_LENGTH> },
         NoConnection,
         InvalidBytes,
         InvalidSentence,
+        ParseError(NmeaError),
+    }
+
+    // The parsing below is shared by the blocking Gps and the AsyncGps, only reading the port differs.
+
+    /// Adds a byte read from the port to the line. True when the line is finished: a \n was read or
+    /// the line is max_length long.
+    pub(crate) fn push_byte(output: &mut Vec<u8, MAX_SENTENCE_LENGTH>, byte: u8, max_length: usize) -> bool {
+        let _ = output.push(byte);
+        byte == 10u8 || output.len() >= max_length || output.is_full()
+    }
+
+    /// Gives a finished line as Valid if it's utf8.
+    pub(crate) fn finish_line(output: Vec<u8, MAX_SENTENCE_LENGTH>) -> PortConnection {
+        if str::from_utf8(&output).is_ok() {
+            PortConnection::Valid(String::from_utf8(output).unwrap())
+        }
+        else {
+            PortConnection::InvalidBytes(output)
+        }
+    }
+
+    /// Parses the fields of a sentence, the header included, for SentenceParsers.
+    pub type SentenceParser = fn(Fields) -> Result<GpsSentence, NmeaError>;
+
+    /// Most parsers SentenceParsers can hold.
+    pub const MAX_PARSERS: usize = 8;
+
+    /// Parsers an application adds for sentences of its own, by sentence ID.
+    ///
+    /// The ID is the header without the $, eg "PGTOP", or the sentence type of any talker, eg "TXT".
+    /// They are tried before the sentences parsed here, so they can replace those too. Whatever
+    /// the parser gives is given by update, an Err as GpsSentence::ParseError.
+    /// ```ignore
+    /// fn parse_txt(mut fields: Fields) -> Result<GpsSentence, NmeaError> {
+    ///     fields.check_header("TXT")?;
+    ///     ...
+    /// }
+    /// gps.parsers.register("TXT", parse_txt).unwrap();
+    /// ```
+    #[derive(Debug, Default, Clone)]
+    pub struct SentenceParsers {
+        parsers: Vec<(&'static str, SentenceParser), MAX_PARSERS>,
+    }
+
+    impl SentenceParsers {
+        pub const fn new() -> SentenceParsers {
+            SentenceParsers { parsers: Vec::new() }
+        }
+
+        /// Adds the parser for the sentence ID, replacing one already there. Gives the parser back
+        /// if MAX_PARSERS are already registered.
+        pub fn register(&mut self, id: &'static str, parser: SentenceParser) -> Result<(), SentenceParser> {
+            match self.parsers.iter_mut().find(|(registered, _parser)| *registered == id) {
+                Some(registered) => {
+                    registered.1 = parser;
+                    Ok(())
+                }
+                None => self.parsers.push((id, parser)).map_err(|(_id, parser)| parser),
+            }
+        }
+
+        /// The parser for a header, eg "$GPTXT".
+        fn get(&self, header: &str) -> Option<SentenceParser> {
+            let id = header.get(1..).unwrap_or("");
+            let sentence_type = if id.len() == 5 { id.get(2..) } else { None };
+            self.parsers.iter()
+                .find(|(registered, _parser)| *registered == id || Some(*registered) == sentence_type)
+                .map(|(_id, parser)| *parser)
+        }
+
+        /// Parses one sentence like GpsSentence::from_sentence, with these parsers.
+        pub fn parse(&self, sentence: &str) -> GpsSentence {
+            let mut line = String::new();
+            if line.push_str(sentence).is_err() {
+                return GpsSentence::InvalidSentence;
+            }
+            parse_line(PortConnection::Valid(line), None, self).unwrap_or(GpsSentence::InvalidSentence)
+        }
+    }
+
+    /// A sentence that isn't parsed as it is.
+    fn unknown_sentence(mut fields: Fields) -> GpsSentence {
+        let mut header = String::new();
+        if header.push_str(fields.next().unwrap_or("").trim_start_matches('$')).is_err() {
+            return GpsSentence::ParseError(NmeaError::WrongSentence);
+        }
+        let mut rest = String::new();
+        let _ = rest.push_str(fields.remaining()); // Shorter than the line, so it fits.
+        GpsSentence::Unknown { header, fields: rest }
+    }
+
+    /// Parses a line read by update.
+    ///
+    /// With a GsvAssembler, a GSV message is added to its talker's set and None is given until the
+    /// set is finished. Without one, a GSV gives the satellites of just that message.
+    pub(crate) fn parse_line(port_output: PortConnection, gsv: Option<&mut GsvAssembler>, parsers: &SentenceParsers) -> Option<GpsSentence> {
+        let string = match port_output {
+            PortConnection::NoConnection => return Some(GpsSentence::NoConnection),
+            PortConnection::InvalidBytes(_vector) => return Some(GpsSentence::InvalidBytes),
+            PortConnection::Valid(string) => string,
+        };
+        let sentence = match parse_sentence(string.as_str()) {
+            Some(sentence) => sentence,
+            None => return Some(GpsSentence::InvalidSentence),
+        };
+        // At this point sentences needs to be is_valid str.
+        if let Some(parser) = sentence.clone().next().and_then(|header| parsers.get(header)) {
+            return Some(parser(sentence).unwrap_or_else(GpsSentence::ParseError));
+        }
+        if string.starts_with("$PMTK") {
+            return Some(parse_pmtk(sentence).unwrap_or_else(GpsSentence::ParseError));
+        }
+        let talker = sentence.talker();
+        let parsed = match sentence.sentence_type().unwrap_or("") {
+            "GGA" => parse_gga(sentence).map(|gga| GpsSentence::GGA(talker, gga)),
+            "VTG" => parse_vtg(sentence).map(|vtg| GpsSentence::VTG(talker, vtg)),
+            "GSA" => parse_gsa(sentence).map(|gsa| GpsSentence::GSA(talker, gsa)),
+            "GLL" => parse_gll(sentence).map(|gll| GpsSentence::GLL(talker, gll)),
+            "RMC" => parse_rmc(sentence).map(|rmc| GpsSentence::RMC(talker, rmc)),
+            "ZDA" => parse_zda(sentence).map(|zda| GpsSentence::ZDA(talker, zda)),
+            "GNS" => parse_gns(sentence).map(|gns| GpsSentence::GNS(talker, gns)),
+            "GST" => parse_gst(sentence).map(|gst| GpsSentence::GST(talker, gst)),
+            "GSV" => match (parse_gsv_message(sentence), gsv) {
+                (Ok(message), Some(gsv)) => match gsv.push(talker, message) {
+                    Ok(set) => return set.map(|gsv_data| GpsSentence::GSV(talker, GsvSet::from(gsv_data))),
+                    Err(e) => Err(e),
+                },
+                (Ok(message), None) => {
+                    let mut gsv_data = GsvData { satellites_in_view: message.satellites_in_view, satellites: Vec::new(), signal_id: message.signal_id };
+                    let _ = gsv_data.satellites.extend_from_slice(&message.satellites); // 4 fit in MAX_SATELLITES.
+                    Ok(GpsSentence::GSV(talker, GsvSet::from(gsv_data)))
+                }
+                (Err(e), _) => Err(e),
+            },
+            _ => Ok(unknown_sentence(sentence)),
+        };
+        Some(parsed.unwrap_or_else(GpsSentence::ParseError))
     }
 
     /// This is the main struct around which all commands are centered. It allows for communication
     /// with the GPS module via the open port.
     ///
+    /// The port can be anything that implements GpsPort: a serial port (see Gps::new), a pipe,
+    /// a TCP stream from ser2net, a pty or an in-memory buffer.
+    ///
     /// Satellite data: true if you want the individual satellite data
     /// Navigation data: true if you want the navigation data (lat, long, etc)
-    pub struct Gps {
-        pub port: Box<dyn SerialPort>,
+    ///
+    /// GSV messages are put together by gsv, see [GsvAssembler](nmea/gsv/struct.GsvAssembler.html).
+    /// Sentences of your own are parsed by parsers, see [SentenceParsers](struct.SentenceParsers.html).
+    pub struct Gps<T> {
+        pub port: T,
+        pub options: GpsOptions,
+        pub gsv: GsvAssembler,
+        pub parsers: SentenceParsers,
     }
 
-    impl Gps {
-        pub fn new(port: &str, baud_rate: &str) -> Gps {
-            Gps { port: open_port(port, baud_rate.parse().unwrap()) }
+    #[cfg(feature = "std")]
+    impl Gps<Box<dyn SerialPort>> {
+        /// Opens the serial port and gives a Gps reading from it. Default baud rate is 9600.
+        pub fn new(port: &str, baud_rate: &str) -> Result<Gps<Box<dyn SerialPort>>, Error> {
+            Gps::new_with_options(port, baud_rate, GpsOptions::default())
+        }
+
+        /// Opens the serial port with the port time out of the options, and reads with the rest.
+        pub fn new_with_options(port: &str, baud_rate: &str, options: GpsOptions) -> Result<Gps<Box<dyn SerialPort>>, Error> {
+            let baud_rate = baud_rate.parse().map_err(|_e| Error::BadBaudRate)?;
+            Ok(Gps { port: open_port_with_timeout(port, baud_rate, options.port_timeout)?, options, gsv: GsvAssembler::new(), parsers: SentenceParsers::new() })
+        }
+    }
+
+    impl<T: GpsPort> Gps<T> {
+        /// Gives a Gps reading from and writing to any byte transport.
+        pub fn from_port(port: T) -> Gps<T> {
+            Gps { port, options: GpsOptions::default(), gsv: GsvAssembler::new(), parsers: SentenceParsers::new() }
+        }
+
+        /// Reads with the no data time out and sentence length of the options. The port time out
+        /// is up to whatever opened the port.
+        pub fn with_options(mut self, options: GpsOptions) -> Gps<T> {
+            self.options = options;
+            self
         }
 
         /// Reads a full sentence from the serial buffer, returns a String.
@@ -116,103 +416,125 @@ pub mod gps {
             // from the serial buffer is the size of the buffer vec.
 
             // 127 is the maximum valid utf8 number.
-            let mut buffer: Vec<u8> = vec![0; 1]; // Reads what is in the buffer, be it nothing or max.
-            let mut output: Vec<u8> = Vec::new();
+            let mut buffer: [u8; 1] = [0; 1]; // Reads what is in the buffer, be it nothing or max.
+            let mut output: Vec<u8, MAX_SENTENCE_LENGTH> = Vec::new();
+            let options = self.options;
             let p = &mut self.port;
             let mut cont = true;
+            #[cfg(feature = "std")]
             let start = SystemTime::now();
             while cont {
-                // If there is no connection, this match statement is looped over with a 1 second time out
-                // as given by the port open.
-                if start.elapsed().unwrap() > Duration::from_secs(1) {
-                    return PortConnection::NoConnection;
+                // If there is no connection, this match statement is looped over until the no data
+                // time out.
+                #[cfg(feature = "std")]
+                {
+                    if start.elapsed().unwrap_or_default() > options.no_data_timeout {
+                        return PortConnection::NoConnection;
+                    }
                 }
-                match p.read(buffer.as_mut_slice()) {
-                    Ok(buffer_size) => {
-                        output.extend_from_slice(&buffer[..buffer_size]);
-
-                        if output.get(output.len() - 1).unwrap() == &10u8 || output.len() > 255 {
+                match p.read_bytes(&mut buffer) {
+                    Ok(0) => (),  // Nothing to read yet (or end of a stream), wait for the time out.
+                    Ok(_buffer_size) => {
+                        if push_byte(&mut output, buffer[0], options.max_sentence_length) {
                             cont = false;
                         }
                     }
+                    // Without std the port is the only thing that can tell the time.
+                    #[cfg(not(feature = "std"))]
+                    Err(PortError::TimedOut) => return PortConnection::NoConnection,
                     Err(_e) => (),
                 }
             }
-            let string = str::from_utf8(&output);
-            if let Ok(str) = string {
-                PortConnection::Valid(str.to_string())
-            }
-            else {
-                PortConnection::InvalidBytes(output)
-            }
+            finish_line(output)
         }
 
-        /// Keeps reading sentences until all the required sentences are read.
-        /// Returns GpsData.
+        /// Reads the next sentence. A GSV set is given once all its messages are read, other
+        /// sentences in between are given as they come and the set carries on with the next update.
         pub fn update(&mut self) -> GpsSentence {
-            let port_output = self.read_line();
-
-            return match port_output {
-                PortConnection::NoConnection => GpsSentence::NoConnection,
-                PortConnection::InvalidBytes(_vector) => GpsSentence::InvalidBytes,
-                PortConnection::Valid(string) => {
-                    let sentence: Option<Vec<&str>> = parse_sentence(string.as_str());
-                    if sentence.is_some() {
-                        let sentence = sentence.unwrap();
-                        let header = sentence.get(0).unwrap();
-                        // At this point sentences needs to be is_valid str.
-                        if &header[3..5] == "GG" {
-                            return GpsSentence::GGA(parse_gga(sentence));
-                        } else if &header[3..6] == "VTG" {
-                            return GpsSentence::VTG(parse_vtg(sentence));
-                        } else if &header[3..6] == "GSA" {
-                            return GpsSentence::GSA(parse_gsa(sentence));
-                        } else if &header[3..6] == "GLL" {
-                            return GpsSentence::GLL(parse_gll(sentence));
-                        } else if &header[3..6] == "RMC" {
-                            return GpsSentence::RMC(parse_rmc(sentence));
-                        } else if &header[3..6] == "GSV" {
-                            // Assumes that each GSV sentence if given in exact sequence, and not out of order.
-                            let number_of_messages: i32 = sentence.get(1).unwrap().parse().unwrap();
-
-                            let mut gsv_values: Vec<Satellites> = parse_gsv(sentence); // First sentence
-                            for _message in 1..number_of_messages { // If number of messages is 1, this is all skipped.
-                                // Read lines and add it for each message.
-                                let line = self.read_line();
-                                if let PortConnection::Valid(line) = line {
-                                    let sentence = parse_sentence(line.as_str());
-                                    let sentence = sentence.unwrap();
-                                    gsv_values.append(parse_gsv(sentence).as_mut())
-                                };
-                            }
-                            return GpsSentence::GSV(gsv_values);
-                        }
-                    }
-                    GpsSentence::InvalidSentence
+            loop {
+                let line = self.read_line();
+                if let Some(sentence) = parse_line(line, Some(&mut self.gsv), &self.parsers) {
+                    return sentence;
+                }
+            }
+        }
+    }
+
+    impl GpsSentence {
+        /// Parses one sentence, eg from the NmeaFramer. A GSV only gives the satellites in that
+        /// one message, put the full set together with a GsvAssembler.
+        pub fn from_sentence(sentence: &str) -> GpsSentence {
+            SentenceParsers::new().parse(sentence)
+        }
+
+        /// The sentence written back as NMEA, eg to turn saved GpsSentences back into an NMEA
+        /// file. A GSV set can take up to gsv::MAX_MESSAGES sentences. NoConnection, InvalidBytes,
+        /// InvalidSentence, ParseError and a PMTK001 NoPacket give none.
+        pub fn to_sentences(&self) -> Vec<String<MAX_SENTENCE_LENGTH>, MAX_MESSAGES> {
+            let mut sentences = Vec::new();
+            let sentence = match self {
+                GpsSentence::GSV(talker, data) => return data.to_sentences(*talker),
+                GpsSentence::GGA(talker, data) => data.to_sentence(*talker),
+                GpsSentence::VTG(talker, data) => data.to_sentence(*talker),
+                GpsSentence::GSA(talker, data) => data.to_sentence(*talker),
+                GpsSentence::GLL(talker, data) => data.to_sentence(*talker),
+                GpsSentence::RMC(talker, data) => data.to_sentence(*talker),
+                GpsSentence::ZDA(talker, data) => data.to_sentence(*talker),
+                GpsSentence::GNS(talker, data) => data.to_sentence(*talker),
+                GpsSentence::GST(talker, data) => data.to_sentence(*talker),
+                GpsSentence::PMTK001(cmd, ack) => match ack.flag() {
+                    Some(flag) => add_checksum(format_command(format_args!("PMTK001,{},{}", cmd, flag)).as_str()),
+                    None => return sentences,
+                },
+                GpsSentence::PMTK010(message) => {
+                    let code = match message {
+                        SystemMessage::Unknown => 0,
+                        SystemMessage::Startup => 1,
+                        SystemMessage::EpoNotification => 2,
+                        SystemMessage::Normal => 3,
+                    };
+                    add_checksum(format_command(format_args!("PMTK010,{:03}", code)).as_str())
                 }
+                GpsSentence::PMTK011(text) => add_checksum(format_command(format_args!("PMTK011,{}", text)).as_str()),
+                GpsSentence::PMTK(packet_type, fields) => {
+                    add_checksum(format_command(format_args!("PMTK{:03},{}", packet_type, fields)).as_str())
+                }
+                GpsSentence::Unknown { header, fields } => {
+                    add_checksum(format_command(format_args!("{},{}", header, fields)).as_str())
+                }
+                GpsSentence::NoConnection | GpsSentence::InvalidBytes | GpsSentence::InvalidSentence
+                | GpsSentence::ParseError(_) => return sentences,
             };
+            let _ = sentences.push(sentence);
+            sentences
         }
     }
 
-    // todo - ensure that appending is done by accident if the same program is run multiple times.
-    // Some kind of init or new()? Make a new file first and then append?
+    #[cfg(feature = "std")]
     impl GpsSentence {
         /// Reads a bytes file of structs to a vector.
         ///
+        /// Each struct is read up to its own length, so a \n (10) byte inside one doesn't split it.
+        /// A struct that can't be read, eg from a log written by an older version, gives
+        /// Error::Io with InvalidData instead of being skipped.
+        ///
         /// Benches at 263,860ns to read a 1,000 long vec.
-        pub fn read_from(file: &str) -> Vec<GpsSentence> {
-            let mut f = File::open(file).expect("No file found");
-            let mut buffer = Vec::new();
-            let _ = f.read_to_end(&mut buffer);
-            let split = buffer.split(|num| num == &10);
-            let mut struct_vec: Vec<GpsSentence> = Vec::new();
-            for item in split {
-                if let Ok(t) = bincode::deserialize(item) {
-                    struct_vec.push(t)
+        pub fn read_from(file: &str) -> Result<std::vec::Vec<GpsSentence>, Error> {
+            let mut f = File::open(file)?;
+            let mut buffer = std::vec::Vec::new();
+            f.read_to_end(&mut buffer)?;
+            let mut rest: &[u8] = &buffer;
+            let mut struct_vec: std::vec::Vec<GpsSentence> = std::vec::Vec::new();
+            while !rest.is_empty() {
+                struct_vec.push(bincode::deserialize_from(&mut rest)?);
+                match rest.split_first() {
+                    Some((10, after)) => rest = after,
+                    _ => return Err(Error::Io(io::Error::new(io::ErrorKind::InvalidData,
+                                                             "no \\n after a GpsSentence"))),
                 }
             }
 
-            return struct_vec;
+            Ok(struct_vec)
         }
 
         /// Append a GpsSentence struct to a file.
@@ -220,24 +542,26 @@ pub mod gps {
         /// individually. You must clone the struct that is being iterated over.
         /// ```
         /// use adafruit_gps::GpsSentence;
-        /// let v: Vec<GpsSentence> = vec![GpsSenence];
+        /// let v: Vec<GpsSentence> = vec![GpsSentence::NoConnection];
         /// for s in v.iter() {
-        ///     s.clone().append_to("vector");
+        ///     s.clone().append_to("vector").unwrap();
         /// }
-        /// let read: Vec<GpsSentence> = GpsSentence::read_from("vec_test");
+        /// let read: Vec<GpsSentence> = GpsSentence::read_from("vector").unwrap();
+        /// # std::fs::remove_file("vector").unwrap();
         /// ```
         ///
         /// Benches at 55,000,000 ns (0.05 s) for a 1,000 long vector, both as append directly
         /// or when iterating over a vector.
         ///
         /// Append with a \n (10) byte at the end so it can be read back into a vector.
-        pub fn append_to(self, file: &str) {
-            let mut f = OpenOptions::new().append(true).create(true).open(file).unwrap();
+        pub fn append_to(self, file: &str) -> Result<(), Error> {
+            let mut f = OpenOptions::new().append(true).create(true).open(file)?;
             // has to open a file if none exist.
 
-            let _ = f.write(serialize(&self).unwrap().as_ref());
+            f.write_all(serialize(&self)?.as_ref())?;
             let breakline: [u8; 1] = [10];
-            let _ = f.write(&breakline);
+            f.write_all(&breakline)?;
+            Ok(())
         }
     }
 }
@@ -274,18 +598,203 @@ mod gps_test {
             false
         );
     }
+
+    #[test]
+    fn non_ascii_sentence() {
+        assert!(!gps::is_valid_checksum("é1*41"));
+        assert!(!gps::is_valid_checksum("$Aé1"));
+        assert!(!gps::is_valid_checksum("$A*é"));
+        assert!(!gps::is_valid_checksum("$GPGSA,1*+F"));
+        // The checksum of the bytes of é.
+        assert!(gps::is_valid_checksum("$é*6A"));
+        assert!(crate::parse_sentence("é1*41").is_none());
+        assert_eq!(gps::GpsSentence::from_sentence("é1*41"), gps::GpsSentence::InvalidSentence);
+        match gps::GpsSentence::from_sentence("$é*6A") {
+            gps::GpsSentence::Unknown { header, .. } => assert_eq!(header.as_str(), "é"),
+            other => panic!("Expected Unknown, got {:?}", other),
+        }
+        // Too long for a sentence ID.
+        assert_eq!(
+            gps::GpsSentence::from_sentence(&crate::pmtk::send_pmtk::add_checksum("ABCDEFGHI,1")),
+            gps::GpsSentence::ParseError(crate::NmeaError::WrongSentence)
+        );
+    }
 }
 
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
+mod generic_port_test {
+    use core::convert::TryFrom;
+    use std::io::{Cursor, Read, Result, Write};
+    use std::time::{Duration, Instant};
+
+    use heapless::String;
+
+    use crate::{Fields, Gps, GpsOptions, GpsSentence, NmeaError, SentenceParsers, Talker};
+    use crate::open_gps::gps::{MAX_SENTENCE_LENGTH, PortConnection};
+
+    /// In memory port: reads from a fixed buffer, records everything written to it.
+    struct MemoryPort {
+        input: Cursor<Vec<u8>>,
+        output: Vec<u8>,
+    }
+
+    impl Read for MemoryPort {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            self.input.read(buf)
+        }
+    }
+
+    impl Write for MemoryPort {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            self.output.write(buf)
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn memory_gps(input: &str) -> Gps<MemoryPort> {
+        Gps::from_port(MemoryPort { input: Cursor::new(input.as_bytes().to_vec()), output: Vec::new() })
+    }
+
+    #[test]
+    fn update_from_memory() {
+        let mut gps = memory_gps("$GNGGA,131613.000,5132.7314,N,00005.9099,W,1,9,1.17,42.4,M,47.0,M,,*60\r\n");
+        match gps.update() {
+            GpsSentence::GGA(talker, gga) => {
+                assert_eq!(talker, Talker::Gnss);
+                assert_eq!(gga.satellites_used, 9);
+            }
+            other => panic!("Expected GGA, got {:?}", other),
+        }
+        assert_eq!(gps.update(), GpsSentence::NoConnection);
+    }
+
+    #[test]
+    fn gsv_set_around_other_sentences() {
+        let mut gps = memory_gps("$GPGSV,2,1,06,10,63,112,23,32,60,232,21,24,39,291,27,21,23,082,*77\r\n\
+            $GNGGA,131613.000,5132.7314,N,00005.9099,W,1,9,1.17,42.4,M,47.0,M,,*60\r\n\
+            $GPGSV,2,2,06,25,21,320,28,20,12,052,*76\r\n\
+            $GPGSV,2,2,06,25,21,320,28,20,12,052,*76\r\n");
+        assert!(matches!(gps.update(), GpsSentence::GGA(Talker::Gnss, _)));
+        match gps.update() {
+            GpsSentence::GSV(talker, gsv) => {
+                assert_eq!(talker, Talker::Gps);
+                assert_eq!(gsv.satellites_in_view, 6);
+                assert_eq!(gsv.satellites.len(), 6);
+            }
+            other => panic!("Expected GSV, got {:?}", other),
+        }
+        assert_eq!(gps.update(), GpsSentence::ParseError(NmeaError::GsvSequence));
+    }
+
+    #[test]
+    fn parse_error_from_memory() {
+        let mut gps = memory_gps("$GNGLL,5132.7314,Q,00005.9099,W,131613.000,A,A*45\r\n");
+        assert_eq!(gps.update(), GpsSentence::ParseError(NmeaError::BadDirection(2)));
+    }
+
+    /// Gives just the text of a TXT, as an Unknown with the header TXT.
+    fn parse_txt(mut fields: Fields) -> core::result::Result<GpsSentence, NmeaError> {
+        fields.check_header("TXT")?;
+        fields.skip_fields(3)?;
+        let mut text = String::new();
+        let _ = text.push_str(fields.next_field()?);
+        Ok(GpsSentence::Unknown { header: String::try_from("TXT").unwrap(), fields: text })
+    }
+
+    #[test]
+    fn unknown_and_registered_sentences() {
+        let lines = "$PGTOP,11,2*6E\r\n$GPTXT,01,01,02,ANTSTATUS=OPEN*2B\r\n$PGTOP,11,2*6F\r\n";
+        let mut gps = memory_gps(lines);
+        match gps.update() {
+            GpsSentence::Unknown { header, fields } => assert_eq!((header.as_str(), fields.as_str()), ("PGTOP", "11,2")),
+            other => panic!("Expected Unknown, got {:?}", other),
+        }
+        match gps.update() {
+            GpsSentence::Unknown { header, fields } => assert_eq!((header.as_str(), fields.as_str()), ("GPTXT", "01,01,02,ANTSTATUS=OPEN")),
+            other => panic!("Expected Unknown, got {:?}", other),
+        }
+        // A bad checksum is still invalid.
+        assert_eq!(gps.update(), GpsSentence::InvalidSentence);
+
+        let mut gps = memory_gps(lines);
+        gps.parsers.register("TXT", parse_txt).unwrap();
+        gps.parsers.register("PGTOP", |_fields| Err(NmeaError::WrongSentence)).unwrap();
+        assert_eq!(gps.update(), GpsSentence::ParseError(NmeaError::WrongSentence));
+        match gps.update() {
+            GpsSentence::Unknown { header, fields } => assert_eq!((header.as_str(), fields.as_str()), ("TXT", "ANTSTATUS=OPEN")),
+            other => panic!("Expected TXT, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn register_parsers() {
+        let mut parsers = SentenceParsers::new();
+        for id in ["A", "B", "C", "D", "E", "F", "G", "H"] {
+            assert!(parsers.register(id, parse_txt).is_ok());
+        }
+        // Replacing one is fine when full.
+        assert!(parsers.register("A", parse_txt).is_ok());
+        assert!(parsers.register("TXT", parse_txt).is_err());
+        assert_eq!(parsers.parse("$GPTXT,01,01,02,ANTSTATUS=OPEN*2B"), GpsSentence::Unknown {
+            header: String::try_from("GPTXT").unwrap(),
+            fields: String::try_from("01,01,02,ANTSTATUS=OPEN").unwrap(),
+        });
+    }
+
+    #[test]
+    fn sentences_written_back() {
+        for line in ["$PGTOP,11,2*6E\r\n", "$PMTK001,220,3*30\r\n", "$PMTK011,MTKGPS*08\r\n",
+            "$GPVTG,89.68,T,,M,0,N,0,K,A*02\r\n"] {
+            let sentence = GpsSentence::from_sentence(line);
+            assert_eq!(sentence.to_sentences(), [line], "{:?}", sentence);
+        }
+        assert!(GpsSentence::InvalidSentence.to_sentences().is_empty());
+    }
+
+    #[test]
+    fn command_to_memory() {
+        let mut gps = memory_gps("$PMTK001,220,3*30\r\n");
+        assert_eq!(gps.pmtk_220_set_nmea_updaterate("1000"), crate::pmtk::send_pmtk::Pmtk001Ack::Success);
+        assert_eq!(gps.port.output, b"$PMTK220,1000*1F\r\n".to_vec());
+    }
+
+    #[test]
+    fn no_data_timeout() {
+        let mut gps = memory_gps("").with_options(GpsOptions::default().no_data_timeout(Duration::from_millis(50)));
+        let start = Instant::now();
+        assert_eq!(gps.update(), GpsSentence::NoConnection);
+        assert!(start.elapsed() < Duration::from_millis(500));
+    }
+
+    #[test]
+    fn max_sentence_length() {
+        let options = GpsOptions::default().max_sentence_length(40);
+        let mut gps = memory_gps("$GNGGA,131613.000,5132.7314,N,00005.9099,W,1,9,1.17,42.4,M,47.0,M,,*60\r\n\
+            $GPVTG,89.68,T,,M,0.00,N,0.0,K*5F\r\n").with_options(options);
+        match gps.read_line() {
+            PortConnection::Valid(line) => assert_eq!(line.as_str(), "$GNGGA,131613.000,5132.7314,N,00005.9099"),
+            other => panic!("Expected a valid line, got {:?}", other),
+        }
+        assert_eq!(gps.update(), GpsSentence::InvalidSentence);
+        assert!(matches!(gps.update(), GpsSentence::VTG(_, _)));
+        assert_eq!(GpsOptions::default().max_sentence_length(1000).max_sentence_length, MAX_SENTENCE_LENGTH);
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
 mod test_read_write {
     use std::fs::remove_file;
 
-    use crate::GpsSentence;
+    use crate::{Error, GpsSentence, Talker};
     use crate::nmea::gga::{GgaData, SatFix};
+    use crate::nmea::datetime::NmeaTime;
 
-    const SENTENCE: GpsSentence = GpsSentence::GGA(GgaData {
-        utc: 100.0,
+    const SENTENCE: GpsSentence = GpsSentence::GGA(Talker::Gps, GgaData {
+        utc: NmeaTime { hour: 0, minute: 1, second: 0, millis: 0 },
         lat: Some(51.55465),
         long: Some(-0.05632),
         sat_fix: SatFix::DgpsFix,
@@ -298,8 +807,8 @@ mod test_read_write {
 
     #[test]
     fn read_write_single() {
-        SENTENCE.append_to("single_test");
-        let read = GpsSentence::read_from("single_test");
+        SENTENCE.append_to("single_test").unwrap();
+        let read = GpsSentence::read_from("single_test").unwrap();
         let _ = remove_file("single_test");
         assert_eq!(read, vec![SENTENCE]);
     }
@@ -308,9 +817,9 @@ mod test_read_write {
     fn read_write_vec() {
         let v: Vec<GpsSentence> = vec![SENTENCE];
         for s in v.iter() {
-            s.clone().append_to("vec_test");
+            s.clone().append_to("vec_test").unwrap();
         }
-        let read: Vec<GpsSentence> = GpsSentence::read_from("vec_test");
+        let read: Vec<GpsSentence> = GpsSentence::read_from("vec_test").unwrap();
         let _ = remove_file("vec_test");
         assert_eq!(v, read);
     }
@@ -319,12 +828,35 @@ mod test_read_write {
     fn read_and_write_loop() {
         let mut check_vec = Vec::new();
         for _ in 0..3 {
-            SENTENCE.append_to("loop_test");
+            SENTENCE.append_to("loop_test").unwrap();
             check_vec.push(SENTENCE)
         }
 
-        let read: Vec<GpsSentence> = GpsSentence::read_from("loop_test");
+        let read: Vec<GpsSentence> = GpsSentence::read_from("loop_test").unwrap();
         let _ = remove_file("loop_test");
         assert_eq!(read, check_vec);
     }
+
+    #[test]
+    fn read_newline_byte() {
+        let mut header = heapless::String::new();
+        header.push_str("$GPTXT").unwrap();
+        let mut fields = heapless::String::new();
+        fields.push_str("line\nbreak").unwrap();
+        let sentence = GpsSentence::Unknown { header, fields };
+        sentence.clone().append_to("newline_test").unwrap();
+        SENTENCE.append_to("newline_test").unwrap();
+        let read = GpsSentence::read_from("newline_test");
+        let _ = remove_file("newline_test");
+        assert_eq!(read.unwrap(), vec![sentence, SENTENCE]);
+    }
+
+    #[test]
+    fn read_bad_log() {
+        // Not a GpsSentence, eg a log from before the format changed.
+        std::fs::write("bad_log_test", [0xFF, 0xFF, 0xFF, 0xFF, 10]).unwrap();
+        let read = GpsSentence::read_from("bad_log_test");
+        let _ = remove_file("bad_log_test");
+        assert!(matches!(read, Err(Error::Io(ref e)) if e.kind() == std::io::ErrorKind::InvalidData));
+    }
 }
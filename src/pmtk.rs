@@ -13,21 +13,36 @@
 //! See [gps impl for all the commands.](../gps/struct.Gps.html)
 //!
 //! ## Changing the baudrate
-//! Given it's a special function, it's a stand alone method in the send_pmtk module.
+//! Given it's a special function, it's a stand alone method in the send_pmtk module. It opens the
+//! serial port itself so it needs the std feature.
 //!
 //! ## PMTK return formats
 //! Depending on the command given, the return values change.
 //!
+//! PMTK sentences read by update are given as GpsSentence too: PMTK001 acks, PMTK010 system
+//! messages (startup, EPO out of date), PMTK011 text and any other reply as GpsSentence::PMTK, so
+//! one read loop sees reboots and late acks without losing navigation data.
+//!
 
 pub mod send_pmtk {
     //! Contains all the pmtk commands that can be sent.
-    use std::str;
-
-    use serialport::{self, ClearBuffer};
-
-    use super::super::open_gps::gps::{Gps,GpsSentence, is_valid_checksum, open_port, PortConnection};
-
-    #[derive(Debug, PartialEq)]
+    use core::convert::TryFrom;
+    use core::fmt::{self, Write as FmtWrite};
+    use core::str;
+    #[cfg(feature = "std")]
+    use std::io::Write;
+
+    use heapless::{String, Vec};
+    use serde::{Serialize, Deserialize};
+    #[cfg(feature = "std")]
+    use serialport::ClearBuffer;
+
+    use super::super::nmea::parse_nmea::{Fields, MAX_FIELDS, NmeaError, parse_sentence};
+    use super::super::open_gps::gps::{Gps, GpsPort, GpsSentence, is_valid_checksum, MAX_SENTENCE_LENGTH, PortConnection};
+    #[cfg(feature = "std")]
+    use super::super::open_gps::gps::open_port;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Copy)]
     /// # PMTK001 return values
     ///
     /// - Invalid (No such command)
@@ -48,6 +63,43 @@ pub mod send_pmtk {
         NoPacket,
     }
 
+    impl Pmtk001Ack {
+        fn from_flag(flag: &str) -> Pmtk001Ack {
+            match flag {
+                "0" => Pmtk001Ack::Invalid,
+                "1" => Pmtk001Ack::Unsupported,
+                "2" => Pmtk001Ack::Failed,
+                "3" => Pmtk001Ack::Success,
+                _ => Pmtk001Ack::NoPacket,
+            }
+        }
+
+        /// The PMTK001 flag, None for NoPacket.
+        pub(crate) fn flag(&self) -> Option<u8> {
+            match self {
+                Pmtk001Ack::Invalid => Some(0),
+                Pmtk001Ack::Unsupported => Some(1),
+                Pmtk001Ack::Failed => Some(2),
+                Pmtk001Ack::Success => Some(3),
+                Pmtk001Ack::NoPacket => None,
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize, Clone, Copy)]
+    /// # PMTK010 system messages
+    ///
+    /// - Unknown (000)
+    /// - Startup (001, the gps has just started)
+    /// - EpoNotification (002, the EPO aiding data needs updating)
+    /// - Normal (003, back to normal mode)
+    pub enum SystemMessage {
+        Unknown,
+        Startup,
+        EpoNotification,
+        Normal,
+    }
+
     #[derive(Debug, PartialEq)]
     /// Dgps (Differential GPS) mode is the usage of ground stations to aid in the accuracy of position.
     /// - NoDGPS: Default
@@ -84,36 +136,312 @@ pub mod send_pmtk {
         pub gga: i8,
         pub gsa: i8,
         pub gsv: i8,
+        pub zda: i8,
         pub pmtkchn_interval: i8,
     }
 
+    /// PMTK707 EPO data: the number of sets, and the GPS week number and time of week (seconds)
+    /// of the first and last set and the first and last set of the current day. -1 if not given.
     #[derive(Debug, PartialEq)]
     pub struct EpoData {
-        pub set: i8,
-        pub fwn_ftow_week_number: i8,
-        pub fwn_ftow_tow: i8,
-        pub lwn_ltow_week_number: i8,
-        pub lwn_ltow_tow: i8,
-        pub fcwn_fctow_week_number: i8,
-        pub fcwn_fctow_tow: i8,
-        pub lcwn_lctow_week_number: i8,
-        pub lcwn_lctow_tow: i8,
+        pub set: i32,
+        pub fwn_ftow_week_number: i32,
+        pub fwn_ftow_tow: i32,
+        pub lwn_ltow_week_number: i32,
+        pub lwn_ltow_tow: i32,
+        pub fcwn_fctow_week_number: i32,
+        pub fcwn_fctow_tow: i32,
+        pub lcwn_lctow_week_number: i32,
+        pub lcwn_lctow_tow: i32,
     }
 
     /// Adds a $ and a checksum to a given string.
-    pub fn add_checksum(sentence: String) -> String {
+    pub fn add_checksum(sentence: &str) -> String<MAX_SENTENCE_LENGTH> {
         let mut checksum = 0;
         for char in sentence.as_bytes() {
             checksum ^= *char;
         }
-        let checksum = format!("{:X}", checksum); //Format as hexidecimal.
-        let checksumed_sentence = format!("${}*{}\r\n", sentence, checksum)
-            .as_str()
-            .to_ascii_uppercase();
+        let mut checksumed_sentence = String::new();
+        // Format as 2 digit hexidecimal. A sentence too long to fit is cut short.
+        let _ = write!(checksumed_sentence, "${}*{:02X}\r\n", sentence, checksum);
         return checksumed_sentence;
     }
 
+    /// Formats a command (no $ and no *checksum) ready for send_command.
+    pub(crate) fn format_command(args: fmt::Arguments) -> String<MAX_SENTENCE_LENGTH> {
+        let mut cmd = String::new();
+        let _ = cmd.write_fmt(args);
+        cmd
+    }
+
+    /// Splits a sentence along ','. Fields past MAX_FIELDS are dropped.
+    fn split_fields(sentence: &str) -> Vec<&str, MAX_FIELDS> {
+        sentence.split(",").take(MAX_FIELDS).collect()
+    }
+
+    /// The field at index as a number, default if it's missing or not a number.
+    fn field_or<T: str::FromStr>(args: &[&str], index: usize, default: T) -> T {
+        args.get(index).and_then(|arg| arg.parse().ok()).unwrap_or(default)
+    }
+
+    // Reading the replies is shared by the blocking Gps and the AsyncGps.
+
+    /// The ack if the line is a PMTK001 reply, None for any other line.
+    pub(crate) fn pmtk_001_ack(line: &str) -> Option<Pmtk001Ack> {
+        // Format: $PMTK001,{cmd},{flag}*{checksum}
+        if !line.starts_with("$PMTK001") {
+            return None;
+        }
+        match parse_pmtk(parse_sentence(line)?) {
+            Ok(GpsSentence::PMTK001(_cmd, ack)) => Some(ack),
+            _ => None,
+        }
+    }
+
+    /// A PMTK sentence read by update, with the checksum already checked and taken off.
+    pub(crate) fn parse_pmtk(mut fields: Fields) -> Result<GpsSentence, NmeaError> {
+        // Format: $PMTK{packet type},{data}
+        let packet_type: u16 = fields.next_field()?.get(5..)
+            .and_then(|packet_type| packet_type.parse().ok())
+            .ok_or(NmeaError::BadNumber(0))?;
+        Ok(match packet_type {
+            1 => {
+                let cmd: u16 = fields.next_required()?;
+                GpsSentence::PMTK001(cmd, Pmtk001Ack::from_flag(fields.next_field()?))
+            }
+            10 => GpsSentence::PMTK010(match fields.next_required::<u16>()? {
+                1 => SystemMessage::Startup,
+                2 => SystemMessage::EpoNotification,
+                3 => SystemMessage::Normal,
+                _ => SystemMessage::Unknown,
+            }),
+            // The whole line fits in MAX_SENTENCE_LENGTH, so the rest of it does too.
+            11 => GpsSentence::PMTK011(String::try_from(fields.remaining()).unwrap_or_default()),
+            _ => GpsSentence::PMTK(packet_type, String::try_from(fields.remaining()).unwrap_or_default()),
+        })
+    }
+
+    /// The line without the checksum if it's a PMTK reply, None for any other line.
+    pub(crate) fn pmtk_500_reply(line: &str) -> Option<String<MAX_SENTENCE_LENGTH>> {
+        if line.starts_with("$PMTK") && is_valid_checksum(line) {
+            let line = line.trim();
+            // Remove checksum, a valid checksum is the last 3 bytes.
+            return String::try_from(&line[..line.len() - 3]).ok();
+        }
+        None
+    }
+
+    /// True if the line is the PMTK011 the gps gives when it starts up.
+    pub(crate) fn is_pmtk_startup(line: &str) -> bool {
+        line.starts_with("$PMTK011") && is_valid_checksum(line)
+    }
+
+    /// PMTK501 reply to the dgps mode.
+    pub(crate) fn dgps_mode_reply(reply: Option<&str>) -> DgpsMode {
+        // Should be just one arg: $PMTK501,{0,1,2}
+        match reply.filter(|args| args.len() == 10).and_then(|args| args.chars().last()) {
+            Some('0') => DgpsMode::NoDgps,
+            Some('1') => DgpsMode::RTCM,
+            Some('2') => DgpsMode::WAAS,
+            _ => DgpsMode::Unknown,
+        }
+    }
+
+    /// PMTK513 reply to sbas enabled.
+    pub(crate) fn sbas_reply(reply: Option<&str>) -> Sbas {
+        match reply.filter(|args| args.len() == 10).and_then(|args| args.chars().last()) {
+            Some('0') => Sbas::Disabled,
+            Some('1') => Sbas::Enabled,
+            _ => Sbas::Unknown,
+        }
+    }
+
+    /// PMTK514 reply to the nmea output.
+    pub(crate) fn nmea_output_reply(reply: Option<&str>) -> NmeaOutput {
+        match reply {
+            Some(args) => {
+                let args = split_fields(args);
+                NmeaOutput {
+                    gll: field_or(&args, 1, -1),
+                    rmc: field_or(&args, 2, -1),
+                    vtg: field_or(&args, 3, -1),
+                    gga: field_or(&args, 4, -1),
+                    gsa: field_or(&args, 5, -1),
+                    gsv: field_or(&args, 6, -1),
+                    zda: field_or(&args, 18, -1),
+                    pmtkchn_interval: field_or(&args, 19, -1),
+                }
+            }
+            None => NmeaOutput {
+                gll: -1,
+                rmc: -1,
+                vtg: -1,
+                gga: -1,
+                gsa: -1,
+                gsv: -1,
+                zda: -1,
+                pmtkchn_interval: -1,
+            },
+        }
+    }
+
+    /// PMTK314 command for the nmea output.
+    pub(crate) fn nmea_output_command(output: &NmeaOutput) -> String<MAX_SENTENCE_LENGTH> {
+        format_command(format_args!(
+            "PMTK314,{},{},{},{},{},{},0,0,0,0,0,0,0,0,0,0,0,{},{}",
+            output.gll, output.rmc, output.vtg, output.gga, output.gsa, output.gsv, output.zda, output.pmtkchn_interval
+        ))
+    }
+
+    // The commands with settings, shared by the blocking Gps and the AsyncGps. None is a setting
+    // that can't be sent, eg DgpsMode::Unknown.
+
+    /// PMTK220 command for the update rate.
+    pub(crate) fn update_rate_command(update_rate: &str) -> String<MAX_SENTENCE_LENGTH> {
+        format_command(format_args!("PMTK220,{}", update_rate))
+    }
+
+    /// PMTK301 command for the dgps mode.
+    pub(crate) fn dgps_mode_command(dgps_mode: &DgpsMode) -> Option<String<MAX_SENTENCE_LENGTH>> {
+        match dgps_mode {
+            DgpsMode::NoDgps => Some(format_command(format_args!("PMTK301,0"))),
+            DgpsMode::RTCM => Some(format_command(format_args!("PMTK301,1"))),
+            DgpsMode::WAAS => Some(format_command(format_args!("PMTK301,2"))),
+            DgpsMode::Unknown => None,
+        }
+    }
+
+    /// PMTK313 command for sbas enabled.
+    pub(crate) fn sbas_command(sbas: &Sbas) -> Option<String<MAX_SENTENCE_LENGTH>> {
+        match sbas {
+            Sbas::Enabled => Some(format_command(format_args!("PMTK313,1"))),
+            Sbas::Disabled => Some(format_command(format_args!("PMTK313,0"))),
+            Sbas::Unknown => None,
+        }
+    }
+
+    /// PMTK319 command for the sbas mode.
+    pub(crate) fn sbas_mode_command(sbas_mode: &SbasMode) -> Option<String<MAX_SENTENCE_LENGTH>> {
+        match sbas_mode {
+            SbasMode::Integrity => Some(format_command(format_args!("PMTK319,1"))),
+            SbasMode::Testing => Some(format_command(format_args!("PMTK319,0"))),
+            SbasMode::Unknown => None,
+        }
+    }
+
+    /// PMTK397 command for the nav speed threshold.
+    pub(crate) fn nav_threshold_command(nav_threshold: f32) -> String<MAX_SENTENCE_LENGTH> {
+        format_command(format_args!("PMTK397,{:.1}", nav_threshold))
+    }
+
+    /// PMTK223 command for the periodic mode ephemeris settings.
+    pub(crate) fn al_dee_cfg_command(sv: i8, snr: i8, ext_threshold: i32, ext_gap: i32) -> String<MAX_SENTENCE_LENGTH> {
+        format_command(format_args!("PMTK223,{},{},{},{}", sv, snr, ext_threshold, ext_gap))
+    }
+
+    /// PMTK225 command for the periodic mode.
+    pub(crate) fn periodic_mode_command(
+        run_type: u8,
+        run_time: u32,
+        sleep_time: u32,
+        second_run_time: u32,
+        second_sleep_time: u32,
+    ) -> String<MAX_SENTENCE_LENGTH> {
+        format_command(format_args!(
+            "PMTK225,{},{},{},{},{}",
+            run_type, run_time, sleep_time, second_run_time, second_sleep_time
+        ))
+    }
+
+    /// PMTK286 command for active interference cancellation.
+    pub(crate) fn aic_mode_command(aic: bool) -> String<MAX_SENTENCE_LENGTH> {
+        format_command(format_args!("PMTK286,{}", aic as u8))
+    }
+
+    /// PMTK869 command to enable or disable EASY.
+    pub(crate) fn easy_enable_command(enable_easy: bool) -> String<MAX_SENTENCE_LENGTH> {
+        format_command(format_args!("PMTK869,1,{}", enable_easy as u8))
+    }
+
+    /// PMTK187 command for the LOCUS interval.
+    pub(crate) fn locus_config_command(locus_interval: i8) -> String<MAX_SENTENCE_LENGTH> {
+        format_command(format_args!("PMTK187,1,{}", locus_interval))
+    }
+
+    /// PMTK330 command for the datum.
+    pub(crate) fn datum_command(datum: u16) -> String<MAX_SENTENCE_LENGTH> {
+        format_command(format_args!("PMTK330,{}", datum))
+    }
+
+    /// PMTK351 command for the QZSS NMEA format.
+    pub(crate) fn qzss_nmea_command(enable_qzss: bool) -> String<MAX_SENTENCE_LENGTH> {
+        format_command(format_args!("PMTK351,{}", enable_qzss as u8))
+    }
+
+    /// PMTK352 command to stop QZSS. Enable is 0, the command stops it.
+    pub(crate) fn stop_qzss_command(enable: bool) -> String<MAX_SENTENCE_LENGTH> {
+        format_command(format_args!("PMTK352,{}", !enable as u8))
+    }
+
+    /// PMTK519 reply to the sbas mode.
+    pub(crate) fn sbas_mode_reply(reply: Option<&str>) -> SbasMode {
+        match reply.and_then(|args| args.chars().last()) {
+            Some('0') => SbasMode::Testing,
+            Some('1') => SbasMode::Integrity,
+            _ => SbasMode::Unknown,
+        }
+    }
+
+    /// PMTK705 reply to the firmware release.
+    pub(crate) fn release_reply(reply: Option<&str>) -> String<MAX_SENTENCE_LENGTH> {
+        match reply.and_then(|args| args.get(9..)) {
+            Some(release) => String::try_from(release).unwrap_or_default(),
+            None => String::new(),
+        }
+    }
+
+    /// PMTK707 reply to the epo info.
+    pub(crate) fn epo_reply(reply: Option<&str>) -> EpoData {
+        let args = split_fields(reply.unwrap_or("PMTK"));
+        EpoData {
+            set: field_or(&args, 1, -1),
+            fwn_ftow_week_number: field_or(&args, 2, -1),
+            fwn_ftow_tow: field_or(&args, 3, -1),
+            lwn_ltow_week_number: field_or(&args, 4, -1),
+            lwn_ltow_tow: field_or(&args, 5, -1),
+            fcwn_fctow_week_number: field_or(&args, 6, -1),
+            fcwn_fctow_tow: field_or(&args, 7, -1),
+            lcwn_lctow_week_number: field_or(&args, 8, -1),
+            lcwn_lctow_tow: field_or(&args, 9, -1),
+        }
+    }
+
+    /// PMTK527 reply to the nav speed threshold.
+    pub(crate) fn nav_threshold_reply(reply: Option<&str>) -> f32 {
+        match reply {
+            Some(args) => field_or(&split_fields(args), 1, -1.0),
+            None => -1.0,
+        }
+    }
+
+    /// PMTK869 reply to the EASY query.
+    pub(crate) fn easy_reply(reply: Option<&str>) -> bool {
+        match reply {
+            Some(args) => split_fields(args).get(2) != Some(&"0"),
+            None => true,
+        }
+    }
+
+    /// PMTK530 reply to the datum.
+    pub(crate) fn datum_reply(reply: Option<&str>) -> u16 {
+        match reply {
+            Some(args) => field_or(&split_fields(args), 1, 0),
+            None => 0,
+        }
+    }
+
     /// Success (new baud rate) or fail.
+    #[cfg(feature = "std")]
     #[derive(Debug, PartialEq)]
     pub enum BaudRateResults {
         Success(u32),
@@ -125,9 +453,10 @@ pub mod send_pmtk {
     /// update will fail. Therefore change the frequency first (probably to 1000 miliseconds)
     /// and then change the baud rate.
     ///
-    /// Returns BaudRateResults enum: Success(baud rate), Fail.
+    /// Returns BaudRateResults enum: Success(baud rate), Fail. Fail if the port can't be opened.
     ///
     /// Use a battery to maintain settings as this method takes a while to run and is error prone.
+    #[cfg(feature = "std")]
     pub fn set_baud_rate(baud_rate: &str, port_name: &str) -> BaudRateResults {
         // stty -F /dev/serial0 9600 clocal cread cs8 -cstopb -parenb
 
@@ -138,8 +467,11 @@ pub mod send_pmtk {
         // For some reason there are invalid bytes in front of what should be the correct baud rate.
         // So read 200 bytes, and ditch the first 100.
         for rate in possible_baud_rates.iter() {
-            let port = open_port(port_name, *rate);
-            let mut gps = Gps { port };
+            let port = match open_port(port_name, *rate) {
+                Ok(port) => port,
+                Err(_e) => return BaudRateResults::Fail,
+            };
+            let mut gps = Gps::from_port(port);
             // Try reading 5 lines.
             for _ in 0..5 {
                 let line = gps.update();
@@ -149,7 +481,7 @@ pub mod send_pmtk {
                     GpsSentence::InvalidBytes => {}
                     _ => {
                         gps.pmtk_220_set_nmea_updaterate("1000");
-                        let cmd = add_checksum(format!("PMTK251,{}", baud_rate));
+                        let cmd = add_checksum(format!("PMTK251,{}", baud_rate).as_str());
                         let cmd = cmd.as_bytes();
                         let _ = gps.port.clear(ClearBuffer::Output);
                         let _ = gps.port.write(cmd);
@@ -162,15 +494,13 @@ pub mod send_pmtk {
     }
 
     /// This implies all the traits to do with sending commands to the gps.
-    impl Gps {
-        #[allow(unused_must_use)] // self.port.write is not used
+    impl<T: GpsPort> Gps<T> {
         /// Send the PMTK command.
         pub fn send_command(&mut self, cmd: &str) {
             //! Input: no $ and no *checksum.
-            let cmd = add_checksum(cmd.to_string());
+            let cmd = add_checksum(cmd);
             let byte_cmd = cmd.as_bytes();
-            self.port.clear(serialport::ClearBuffer::Output);
-            self.port.write_all(byte_cmd);
+            let _ = self.port.write_bytes(byte_cmd);
         }
 
         /// Check for a PMTK001 return.
@@ -181,33 +511,8 @@ pub mod send_pmtk {
                 let line = self.read_line();
                 match line {
                     PortConnection::Valid(line) => {
-                        if is_valid_checksum(line.as_str()) {
-                            if &line[0..8] == "$PMTK001" {
-                                let line = line.trim();
-                                // Remove checksum.
-                                let line: Vec<&str> = line.split("*").collect();
-                                let line: &str = line.get(0).unwrap();
-
-                                let args: Vec<&str> = line.split(",").collect();
-                                // args: $PMTK001, cmd, flag,
-                                // let cmd: &str = args.get(1).expect("pmtk001 format not correct");
-                                let flag: &str = args.get(2).expect("pmtk001 format not correct");
-                                // let value: &str = args.get(3).unwrap_or(&"");
-
-                                return if flag == "0" {
-                                    Pmtk001Ack::Invalid
-                                } else if flag == "1" {
-                                    Pmtk001Ack::Unsupported
-                                } else if flag == "2" {
-                                    Pmtk001Ack::Failed
-                                } else if flag == "3" {
-                                    Pmtk001Ack::Success
-                                } else {
-                                    Pmtk001Ack::NoPacket
-                                };
-                            } else {
-                                continue;
-                            }
+                        if let Some(ack) = pmtk_001_ack(line.as_str()) {
+                            return ack;
                         }
                     }
                     PortConnection::NoConnection => {
@@ -222,19 +527,15 @@ pub mod send_pmtk {
         }
 
         /// Check for PMTK500 style return.
-        pub fn pmtk_500(&mut self) -> Option<String> {
+        pub fn pmtk_500(&mut self) -> Option<String<MAX_SENTENCE_LENGTH>> {
             //! Return the string without checksum.
             for _i in 0..10 {
                 // Check 10 lines before giving up.
                 let line = self.read_line();
                 match line {
                     PortConnection::Valid(line) => {
-                        if (&line[0..5] == "$PMTK") && (is_valid_checksum(&line)) {
-                            let line = line.trim();
-                            // Remove checksum.
-                            let line: Vec<&str> = line.split("*").collect();
-                            let line: &str = line.get(0).unwrap();
-                            return Some(line.to_string());
+                        if let Some(reply) = pmtk_500_reply(line.as_str()) {
+                            return Some(reply);
                         }
                     }
                     PortConnection::NoConnection => {
@@ -254,7 +555,7 @@ pub mod send_pmtk {
                 let line = self.read_line();
                 match line {
                     PortConnection::Valid(line) => {
-                        if (&line[0..8] == "$PMTK011") && (is_valid_checksum(&line)) {
+                        if is_pmtk_startup(line.as_str()) {
                             return true;
                         }
                     }
@@ -295,47 +596,22 @@ pub mod send_pmtk {
 
         /// Set the update rate, as miliseconds from 100 (100Hz) to 10_000 (0.1Hz). 1000 is default.
         pub fn pmtk_220_set_nmea_updaterate(&mut self, update_rate: &str) -> Pmtk001Ack {
-            self.send_command(format!("PMTK220,{}", update_rate).as_str());
+            self.send_command(&update_rate_command(update_rate));
             self.pmtk_001(10)
         }
 
         /// Set Differental Gps mode
         pub fn pmtk_301_api_set_dgps_mode(&mut self, dgps_mode: DgpsMode) -> Pmtk001Ack {
-            match dgps_mode {
-                DgpsMode::NoDgps => self.send_command("PMTK301,0"),
-                DgpsMode::RTCM => self.send_command("PMTK301,1"),
-                DgpsMode::WAAS => self.send_command("PMTK301,2"),
-                DgpsMode::Unknown => (),
+            if let Some(cmd) = dgps_mode_command(&dgps_mode) {
+                self.send_command(&cmd);
             }
-
             self.pmtk_001(10)
         }
 
         /// Check what the current Differential Gps mode is.
         pub fn pmtk_401_api_q_dgps_mode(&mut self) -> DgpsMode {
             self.send_command("PMTK401");
-
-            // Should be just one arg.
-            return match self.pmtk_500() {
-                Some(args) => {
-                    if args.len() != 10 {
-                        // $PM TK5 01, {0,1,2}
-                        return DgpsMode::Unknown;
-                    }
-                    let mode: String = args.chars().nth_back(0).unwrap().to_string();
-                    let mode: &str = mode.as_str();
-                    if mode == "0" {
-                        return DgpsMode::NoDgps;
-                    } else if mode == "1" {
-                        DgpsMode::RTCM
-                    } else if mode == "2" {
-                        DgpsMode::WAAS
-                    } else {
-                        DgpsMode::Unknown
-                    }
-                }
-                None => DgpsMode::Unknown,
-            };
+            dgps_mode_reply(self.pmtk_500().as_deref())
         }
 
         /// Set SBAS (Satellite-based augmentation systems) enabled or disabled.
@@ -343,10 +619,8 @@ pub mod send_pmtk {
             //! Enable = 1 -> Default.
             //!
             //! Disabled = 0
-            match sbas {
-                Sbas::Enabled => self.send_command("PMTK313,1"),
-                Sbas::Disabled => self.send_command("PMTK313,0"),
-                Sbas::Unknown => (),
+            if let Some(cmd) = sbas_command(&sbas) {
+                self.send_command(&cmd);
             }
             self.pmtk_001(10)
         }
@@ -354,23 +628,7 @@ pub mod send_pmtk {
         /// Check if SBAS is enabled
         pub fn pmtk_413_api_q_sbas_enabled(&mut self) -> Sbas {
             self.send_command("PMTK413");
-            return match self.pmtk_500() {
-                Some(args) => {
-                    if args.len() != 10 {
-                        return Sbas::Unknown;
-                    }
-                    let mode = args.chars().nth_back(0).unwrap().to_string();
-                    let mode = mode.as_str();
-                    if mode == "0" {
-                        Sbas::Disabled
-                    } else if mode == "1" {
-                        Sbas::Enabled
-                    } else {
-                        Sbas::Unknown
-                    }
-                }
-                None => Sbas::Unknown,
-            };
+            sbas_reply(self.pmtk_500().as_deref())
         }
 
         /// Set what NMEA sentences are to be outputted as frequency.
@@ -384,20 +642,14 @@ pub mod send_pmtk {
         pub fn pmtk_314_api_set_nmea_output(&mut self, output: NmeaOutput) -> Pmtk001Ack {
             //! 19 fields can be parsed to this one.
             //!
-            //! $PMTK314,{GPGLL},{GPRMC},{GPTVG},{GPGGA},{GPGAS},{GPGSV},{R}..6-17,{PMTKCHN interval}
+            //! $PMTK314,{GPGLL},{GPRMC},{GPTVG},{GPGGA},{GPGAS},{GPGSV},{R}..6-16,{GPZDA},{PMTKCHN interval}
             //!
             //! For each field, frequency setting is given: 0-5, 0-> Disabled,
             //! 1-> Output once everty one position fix, 2-> every second... every 5th.
             //!
             //! Default is PMTK314,-1* (Default: 0,1,1,1,1,5,0..0)
 
-            self.send_command(
-                format!(
-                    "PMTK314,{},{},{},{},{},{},0,0,0,0,0,0,0,{}",
-                    output.gll, output.rmc, output.vtg, output.gga, output.gsa, output.gsv, output.pmtkchn_interval
-                )
-                    .as_str(),
-            );
+            self.send_command(&nmea_output_command(&output));
             self.pmtk_001(10)
         }
 
@@ -406,37 +658,7 @@ pub mod send_pmtk {
             //! Return 514: PMTK514, the nmea outputs that are valid (see pmtk_314_api_set_nmea_output
             //! for the fields).
             self.send_command("PMTK414");
-            return match self.pmtk_500() {
-                Some(args) => {
-                    let args: Vec<&str> = args.split(",").collect();
-                    let gll: &str = args.get(1).unwrap_or(&"-1");
-                    let rmc: &str = args.get(2).unwrap_or(&"-1");
-                    let vtg: &str = args.get(3).unwrap_or(&"-1");
-                    let gga: &str = args.get(4).unwrap_or(&"-1");
-                    let gsa: &str = args.get(5).unwrap_or(&"-1");
-                    let gsv: &str = args.get(6).unwrap_or(&"-1");
-                    let pmtkchn_interval: &str = args.get(18).unwrap_or(&"-1");
-
-                    NmeaOutput {
-                        gll: gll.parse::<i8>().unwrap(),
-                        rmc: rmc.parse::<i8>().unwrap(),
-                        vtg: vtg.parse::<i8>().unwrap(),
-                        gga: gga.parse::<i8>().unwrap(),
-                        gsa: gsa.parse::<i8>().unwrap(),
-                        gsv: gsv.parse::<i8>().unwrap(),
-                        pmtkchn_interval: pmtkchn_interval.parse::<i8>().unwrap(),
-                    }
-                }
-                None => NmeaOutput {
-                    gll: -1,
-                    rmc: -1,
-                    vtg: -1,
-                    gga: -1,
-                    gsa: -1,
-                    gsv: -1,
-                    pmtkchn_interval: -1,
-                },
-            };
+            nmea_output_reply(self.pmtk_500().as_deref())
         }
 
         /// Set SBAS mode
@@ -446,10 +668,8 @@ pub mod send_pmtk {
             //!
             //! Get's reboot code.
             //!
-            match sbas_mode {
-                SbasMode::Integrity => self.send_command("PMTK391,1"),
-                SbasMode::Testing => self.send_command("PMTK391,0"),
-                SbasMode::Unknown => (),
+            if let Some(cmd) = sbas_mode_command(&sbas_mode) {
+                self.send_command(&cmd);
             }
             self.pmtk_startup()
         }
@@ -460,32 +680,16 @@ pub mod send_pmtk {
             //! false: testing mode, true: integrity mode.
             //!
             self.send_command("PMTK419");
-            return match self.pmtk_500() {
-                Some(args) => {
-                    let arg = args.chars().nth_back(0).unwrap().to_string();
-                    let arg = arg.as_str();
-                    if arg == "0" {
-                        SbasMode::Testing
-                    } else if arg == "1" {
-                        SbasMode::Integrity
-                    } else {
-                        SbasMode::Unknown
-                    }
-                }
-                None => SbasMode::Unknown,
-            };
+            sbas_mode_reply(self.pmtk_500().as_deref())
         }
 
         /// Gives GPS firmware release info.
-        pub fn pmtk_605_q_release(&mut self) -> String {
+        pub fn pmtk_605_q_release(&mut self) -> String<MAX_SENTENCE_LENGTH> {
             //! Return example: $PMTK705,AXN_5.1.7_3333_19020118,0027,PA1010D,1.0*76
             //!
             //! Return blank string if no info found.
             self.send_command("PMTK605");
-            return match self.pmtk_500() {
-                Some(args) => args[9..args.len()].to_string(),
-                None => "".to_string(),
-            };
+            release_reply(self.pmtk_500().as_deref())
         }
 
         /// Get EPO data: Extended Prediction Orbit tries to predict where satellites will be in the future.
@@ -505,21 +709,8 @@ pub mod send_pmtk {
             //! - 7 LCWN & LCTOW : GPS week number
             //! - 8 LCWN & LCTOW : TOW of the last set of EPO data that are currently used respectively
 
-            let args = self
-                .pmtk_500()
-                .unwrap_or("PMTK,-1,-1,-1,-1,-1,-1,-1,-1,-1".to_string());
-            let args: Vec<&str> = args.split(",").collect();
-            return EpoData {
-                set: args.get(1).unwrap_or(&"-1").parse::<i8>().unwrap(),
-                fwn_ftow_week_number: args.get(2).unwrap_or(&"-1").parse::<i8>().unwrap(),
-                fwn_ftow_tow: args.get(3).unwrap_or(&"-1").parse::<i8>().unwrap(),
-                lwn_ltow_week_number: args.get(4).unwrap_or(&"-1").parse::<i8>().unwrap(),
-                lwn_ltow_tow: args.get(5).unwrap_or(&"-1").parse::<i8>().unwrap(),
-                fcwn_fctow_week_number: args.get(6).unwrap_or(&"-1").parse::<i8>().unwrap(),
-                fcwn_fctow_tow: args.get(7).unwrap_or(&"-1").parse::<i8>().unwrap(),
-                lcwn_lctow_week_number: args.get(8).unwrap_or(&"-1").parse::<i8>().unwrap(),
-                lcwn_lctow_tow: args.get(9).unwrap_or(&"-1").parse::<i8>().unwrap(),
-            };
+            self.send_command("PMTK607");
+            epo_reply(self.pmtk_500().as_deref())
         }
 
         /// Clear EPO data.
@@ -536,7 +727,7 @@ pub mod send_pmtk {
         /// Speed thresholds: 0/ 0.2/ 0.4/ 0.6/ 0.8/ 1.0/1.5/2.0 (m/s)
         pub fn pmtk_397_set_nav_speed_threshold(&mut self, nav_threshold: f32) -> Pmtk001Ack {
             //! For MT3318 and MT3329 chips.
-            self.send_command(format!("PMTK397,{:.1}", nav_threshold).as_str());
+            self.send_command(&nav_threshold_command(nav_threshold));
             self.pmtk_001(10)
         }
 
@@ -547,7 +738,7 @@ pub mod send_pmtk {
         /// Speed thresholds: 0/ 0.2/ 0.4/ 0.6/ 0.8/ 1.0/1.5/2.0 (m/s)
         pub fn pmtk_386_set_nav_speed_threshold(&mut self, nav_threshold: f32) -> Pmtk001Ack {
             //! For MT3339 chips.
-            self.send_command(format!("PMTK397,{:.1}", nav_threshold).as_str());
+            self.send_command(&nav_threshold_command(nav_threshold));
             self.pmtk_001(10)
         }
 
@@ -555,14 +746,7 @@ pub mod send_pmtk {
         pub fn pmtk_447_q_nav_threshold(&mut self) -> f32 {
             //! $PMTK527,{0.40}*04
             self.send_command("PMTK447");
-            return match self.pmtk_500() {
-                Some(args) => {
-                    let args: Vec<&str> = args.split(",").collect();
-                    let nav_threshold: f32 = args.get(1).unwrap().parse::<f32>().unwrap();
-                    nav_threshold
-                }
-                None => return -1.0,
-            };
+            nav_threshold_reply(self.pmtk_500().as_deref())
         }
 
         /// Puts gps on standby mode for power saving. Send any command to wake it up again.
@@ -593,9 +777,7 @@ pub mod send_pmtk {
             //! Extention gap: Default 60000, range 0-3_600_000
             //!
             //! Standard 001 response.
-            self.send_command(
-                format!("PMTK223,{},{},{},{}", sv, snr, ext_threshold, ext_gap).as_str(),
-            );
+            self.send_command(&al_dee_cfg_command(sv, snr, ext_threshold, ext_gap));
             self.pmtk_001(10)
         }
 
@@ -650,13 +832,7 @@ pub mod send_pmtk {
             //!     to achieve balance of positioning accuracy and power consumption.
             //! - 4.This command needs to work normal with some hardware circuits.
             //!
-            self.send_command(
-                format!(
-                    "PMTK223,{},{},{},{},{}",
-                    run_type, run_time, sleep_time, second_run_time, second_sleep_time
-                )
-                    .as_str(),
-            );
+            self.send_command(&periodic_mode_command(run_type, run_time, sleep_time, second_run_time, second_sleep_time));
             self.pmtk_001(10)
         }
 
@@ -665,11 +841,7 @@ pub mod send_pmtk {
         /// True: enable, false: disabled.
         pub fn pmtk_286_cmd_aic_mode(&mut self, aic: bool) -> Pmtk001Ack {
             //! true is enable, false is disable.
-            if aic {
-                self.send_command("PMTK286,1")
-            } else {
-                self.send_command("PMTK286,0")
-            }
+            self.send_command(&aic_mode_command(aic));
             self.pmtk_001(10)
         }
 
@@ -691,11 +863,7 @@ pub mod send_pmtk {
             //! - pmtk,0 -> gives $PMTK869,2,1,3*29
             //! - pmtk,1,0 -> Gives 001 reply.
             //! - pmtk,2,{0,1} -> Gives 001 reply.
-            if enable_easy {
-                self.send_command("PMTK869,1,1")
-            } else {
-                self.send_command("PMTK869,1,0")
-            }
+            self.send_command(&easy_enable_command(enable_easy));
             self.pmtk_001(10)
         }
 
@@ -703,20 +871,14 @@ pub mod send_pmtk {
         pub fn pmtk_869_cmd_easy_query(&mut self) -> bool {
             //! Query the EASY command status. Return true or false, true is enabled, false it disabled.
             self.send_command("PMTK869,0");
-            return match self.pmtk_500() {
-                Some(args) => {
-                    let args: Vec<&str> = args.split(",").collect();
-                    return args.get(2).unwrap() != &"0";
-                }
-                None => true,
-            };
+            easy_reply(self.pmtk_500().as_deref())
         }
 
         /// Configure LOCUS interval, seconds.
         pub fn pmtk_187_locus_config(&mut self, locus_interval: i8) -> Pmtk001Ack {
             //! Locus mode (1 for interval mode) is always on.
             //! Interval, in seconds, is how often to log a data.
-            self.send_command(format!("PMTK187,1,{}", locus_interval).as_str());
+            self.send_command(&locus_config_command(locus_interval));
             self.pmtk_001(10)
         }
 
@@ -731,7 +893,7 @@ pub mod send_pmtk {
             //! ‘2’ = TOKYO-A
             //!
             //! A full list is on the GTOP Datum list, but I can't find it.
-            self.send_command(format!("PMTK330,{}", datum).as_str());
+            self.send_command(&datum_command(datum));
             self.pmtk_001(10)
         }
 
@@ -742,14 +904,7 @@ pub mod send_pmtk {
             //!
             //! 0 is return value if there is an error.
             self.send_command("PMTK430");
-            return match self.pmtk_500() {
-                Some(args) => {
-                    let args: Vec<&str> = args.split(",").collect();
-                    let datum = args.get(1).unwrap_or(&"0").parse::<u16>().unwrap();
-                    datum
-                }
-                None => 0,
-            };
+            datum_reply(self.pmtk_500().as_deref())
         }
 
         /// The receiver support new NMEA format for QZSS. The command allow user enable or disable QZSS
@@ -758,11 +913,7 @@ pub mod send_pmtk {
             //! Sets the output to be the QZSS NMEA format.
             //!
             //! True is enable, false is disable. Default is disable.
-            if enable_qzss {
-                self.send_command("PMTK351,1")
-            } else {
-                self.send_command("PMTK351,0")
-            }
+            self.send_command(&qzss_nmea_command(enable_qzss));
             self.pmtk_001(10)
         }
 
@@ -774,11 +925,7 @@ pub mod send_pmtk {
             //! Default is enable QZSS function
             //!
             //! Enable is true, disable is false. Default is enable.
-            if enable {
-                self.send_command("PMTK352,0")
-            } else {
-                self.send_command("PMTK352,1")
-            }
+            self.send_command(&stop_qzss_command(enable));
             self.pmtk_001(10)
         }
     }
@@ -793,60 +940,121 @@ mod checksum_test {
     fn checksum() {
         assert_eq!(
             add_checksum(
-                "GNGGA,165419.000,5132.7378,N,00005.9192,W,1,7,1.93,34.4,M,47.0,M,,".to_string()
+                "GNGGA,165419.000,5132.7378,N,00005.9192,W,1,7,1.93,34.4,M,47.0,M,,"
             ),
             "$GNGGA,165419.000,5132.7378,N,00005.9192,W,1,7,1.93,34.4,M,47.0,M,,*6A\r\n"
-                .to_string()
         );
-        assert_eq!(add_checksum("PMTK103".to_string()), "$PMTK103*30\r\n")
+        assert_eq!(add_checksum("PMTK103"), "$PMTK103*30\r\n");
+        // Checksums below 0x10 still take two digits.
+        assert_eq!(add_checksum("PMTK187,1,10"), "$PMTK187,1,10*0C\r\n");
     }
 }
 
 #[cfg(test)]
-mod pmtktests {
-    use std::thread::sleep;
-    use std::time::Duration;
+mod reply_test {
+    use crate::pmtk::send_pmtk::{add_checksum, aic_mode_command, datum_reply, dgps_mode_command, easy_enable_command,
+                                 epo_reply, nav_threshold_command, nav_threshold_reply, nmea_output_reply, periodic_mode_command,
+                                 pmtk_001_ack, pmtk_500_reply, sbas_mode_reply, stop_qzss_command, DgpsMode, Pmtk001Ack, SbasMode};
 
-    use crate::pmtk::send_pmtk::set_baud_rate;
+    #[test]
+    fn commands() {
+        assert_eq!(dgps_mode_command(&DgpsMode::WAAS).unwrap(), "PMTK301,2");
+        assert_eq!(dgps_mode_command(&DgpsMode::Unknown), None);
+        assert_eq!(nav_threshold_command(0.4), "PMTK397,0.4");
+        assert_eq!(aic_mode_command(true), "PMTK286,1");
+        assert_eq!(easy_enable_command(false), "PMTK869,1,0");
+        assert_eq!(periodic_mode_command(0, 0, 0, 0, 0), "PMTK225,0,0,0,0,0");
+        // Enabled QZSS is not stopped.
+        assert_eq!(stop_qzss_command(true), "PMTK352,0");
+    }
 
-    use super::send_pmtk::{DgpsMode, EpoData, NmeaOutput, Pmtk001Ack, Sbas, SbasMode};
-    use super::super::open_gps::gps::{Gps, open_port};
+    #[test]
+    fn pmtk_001() {
+        assert_eq!(pmtk_001_ack(&add_checksum("PMTK001,604,3")), Some(Pmtk001Ack::Success));
+        // No flag, another PMTK and a bad checksum aren't acks.
+        assert_eq!(pmtk_001_ack(&add_checksum("PMTK001,604")), None);
+        assert_eq!(pmtk_001_ack(&add_checksum("PMTK705,AXN_5.1.7")), None);
+        assert_eq!(pmtk_001_ack("$PMTK001,604,3*00"), None);
+    }
 
-    fn port_setup() -> Gps {
-        let _ = set_baud_rate("9600", "/dev/serial0");
-        sleep(Duration::from_secs(1));
-        let port = open_port("/dev/serial0", 9600);
-        let mut gps = Gps { port };
+    #[test]
+    fn bad_replies() {
+        assert_eq!(pmtk_500_reply(&add_checksum("PMTK705,AXN")).unwrap(), "$PMTK705,AXN");
+        assert_eq!(sbas_mode_reply(Some("")), SbasMode::Unknown);
+        assert_eq!(nav_threshold_reply(Some("$PMTK527")), -1.0);
+        assert_eq!(nav_threshold_reply(Some("$PMTK527,x")), -1.0);
+        assert_eq!(datum_reply(Some("$PMTK530,x")), 0);
+        assert_eq!(nmea_output_reply(Some("$PMTK514,0,1,300")).vtg, -1);
+    }
+
+    #[test]
+    fn epo_week_numbers() {
+        let epo = epo_reply(Some("$PMTK707,56,2310,172800,2317,151200,2311,0,2311,86400"));
+        assert_eq!(epo.set, 56);
+        assert_eq!((epo.fwn_ftow_week_number, epo.fwn_ftow_tow), (2310, 172_800));
+        assert_eq!((epo.lcwn_lctow_week_number, epo.lcwn_lctow_tow), (2311, 86_400));
+        assert_eq!(epo_reply(None).lwn_ltow_tow, -1);
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod pmtktests {
+    use crate::emulator::GpsEmulator;
+
+    use super::send_pmtk::{DgpsMode, EpoData, NmeaOutput, Pmtk001Ack, Sbas, SbasMode, SystemMessage};
+    use super::super::open_gps::gps::{Gps, GpsSentence};
+
+    /// The commands are tested against the emulated module, settings as on a PA1010D at 9600 baud.
+    fn port_setup() -> Gps<GpsEmulator> {
+        let mut gps = Gps::from_port(GpsEmulator::new());
         gps.pmtk_220_set_nmea_updaterate("1000");
         return gps;
     }
 
-    #[ignore]
     #[test]
     fn test_pmtk_101_cmd_hot_start() {
         assert_eq!(port_setup().pmtk_101_cmd_hot_start(), true);
     }
 
-    #[ignore]
     #[test]
     fn test_pmtk_102_cmd_warm_start() {
         assert_eq!(port_setup().pmtk_102_cmd_warm_start(), true);
     }
 
-    #[ignore]
     #[test]
     fn test_pmtk_103_cmd_cold_start() {
         assert_eq!(port_setup().pmtk_103_cmd_cold_start(), true);
     }
 
     #[test]
-    #[ignore]
     fn test_pmtk_104_cmd_full_cold_start() {
         assert_eq!(port_setup().pmtk_104_cmd_full_cold_start(), true);
     }
 
     #[test]
-    #[ignore]
+    fn pmtk_sentences_from_update() {
+        let mut gps = port_setup();
+        gps.send_command("PMTK101");
+        gps.send_command("PMTK220,1000");
+        gps.send_command("PMTK605");
+        let pmtk: Vec<GpsSentence> = (0..20).map(|_| gps.update())
+            .filter(|s| match s {
+                GpsSentence::PMTK001(..) | GpsSentence::PMTK010(_) | GpsSentence::PMTK011(_)
+                | GpsSentence::PMTK(..) => true,
+                _ => false,
+            })
+            .collect();
+        assert_eq!(pmtk.len(), 4);
+        assert_eq!(pmtk[0], GpsSentence::PMTK011("MTKGPS".parse().unwrap()));
+        assert_eq!(pmtk[1], GpsSentence::PMTK010(SystemMessage::Startup));
+        assert_eq!(pmtk[2], GpsSentence::PMTK001(220, Pmtk001Ack::Success));
+        match &pmtk[3] {
+            GpsSentence::PMTK(705, release) => assert!(!release.is_empty()),
+            other => panic!("Expected PMTK705, got {:?}", other),
+        }
+    }
+
+    #[test]
     fn test_pmtk_220_set_nmea_updaterate() {
         assert_eq!(
             port_setup().pmtk_220_set_nmea_updaterate("1000"),
@@ -859,7 +1067,6 @@ mod pmtktests {
     }
 
     #[test]
-    #[ignore]
     fn test_pmtk_301_api_set_dgps_mode() {
         assert_eq!(
             port_setup().pmtk_301_api_set_dgps_mode(DgpsMode::NoDgps),
@@ -868,13 +1075,11 @@ mod pmtktests {
     }
 
     #[test]
-    #[ignore]
     fn test_pmtk_401_api_q_dgps_mode() {
         assert_eq!(port_setup().pmtk_401_api_q_dgps_mode(), DgpsMode::WAAS);
     }
 
     #[test]
-    #[ignore]
     fn test_pmtk_313_api_set_sbas_enabled() {
         assert_eq!(
             port_setup().pmtk_313_api_set_sbas_enabled(Sbas::Enabled),
@@ -883,7 +1088,6 @@ mod pmtktests {
     }
 
     #[test]
-    #[ignore]
     fn test_pmtk_413_api_q_sbas_enabled() {
         assert_eq!(port_setup().pmtk_413_api_q_sbas_enabled(), Sbas::Enabled);
     }
@@ -892,7 +1096,6 @@ mod pmtktests {
     // #[ignore]
     // fn test_ () {assert_eq!(port_setup().pmtk_314_api_set_nm(gll: i8, rmc: i8, vtg: i8, gga: i8, gsa: i8, gsv: i8, pmtkchn_interval: i8), Pmtk001Ack::Success);}
     #[test]
-    #[ignore]
     fn test_pmtk_414_api_q_nmea_output() {
         assert_eq!(
             port_setup().pmtk_414_api_q_nmea_output(),
@@ -903,13 +1106,13 @@ mod pmtktests {
                 gga: 1,
                 gsa: 1,
                 gsv: 5,
+                zda: 0,
                 pmtkchn_interval: 0,
             }
         );
     }
 
     #[test]
-    #[ignore]
     fn test_pmtk_319_api_set_sbas_mode() {
         assert_eq!(
             port_setup().pmtk_319_api_set_sbas_mode(SbasMode::Integrity),
@@ -918,28 +1121,24 @@ mod pmtktests {
     }
 
     #[test]
-    #[ignore]
     fn test_pmtk_419_api_q_sbas_mode() {
         assert_eq!(port_setup().pmtk_419_api_q_sbas_mode(), SbasMode::Integrity);
     }
 
     #[test]
-    #[ignore]
     fn test_pmtk_605_q_release() {
         assert_eq!(
             port_setup().pmtk_605_q_release(),
-            "AXN_5.1.7_3333_19020118,0027,PA1010D,1.0".to_string()
+            "AXN_5.1.7_3333_19020118,0027,PA1010D,1.0"
         );
     }
 
     #[test]
-    #[ignore]
     fn test_pmtk_127_cmd_clear_epo() {
         assert_eq!(port_setup().pmtk_127_cmd_clear_epo(), Pmtk001Ack::Success);
     }
 
     #[test]
-    #[ignore]
     fn test_pmtk_607_q_epo_info() {
         assert_eq!(
             port_setup().pmtk_607_q_epo_info(),
@@ -958,7 +1157,6 @@ mod pmtktests {
     }
 
     #[test]
-    #[ignore]
     fn test_pmtk_397_set_nav_speed_threshold() {
         assert_eq!(
             port_setup().pmtk_397_set_nav_speed_threshold(0.2),
@@ -975,7 +1173,6 @@ mod pmtktests {
     }
 
     #[test]
-    #[ignore]
     fn test_pmtk_386_set_nav_speed_threshold() {
         assert_eq!(
             port_setup().pmtk_386_set_nav_speed_threshold(0.2),
@@ -984,25 +1181,25 @@ mod pmtktests {
     }
 
     #[test]
-    #[ignore]
     fn test_pmtk_447_q_nav_threshold() {
         assert_eq!(port_setup().pmtk_447_q_nav_threshold(), 0.0);
     }
 
     // fn test_ () {assert_eq!(port_setup().pmtk_161_cmd_standby_mode(), Pmtk001Ack::Success);}
     #[test]
-    #[ignore]
     fn test_pmtk_223_set_al_dee_cfg() {
-        assert_eq!(
-            port_setup().pmtk_223_set_al_dee_cfg(1, 30, 180000, 60000),
-            Pmtk001Ack::Success
-        );
+        let mut gps = port_setup();
+        assert_eq!(gps.pmtk_223_set_al_dee_cfg(1, 30, 180000, 60000), Pmtk001Ack::Success);
+        assert_eq!(gps.port.commands.last().unwrap(), "PMTK223,1,30,180000,60000");
     }
 
-    // fn test_ () {assert_eq!(port_setup().pmtk_225_cmd_periodic_mode(run_type: u8, run_time: u32, sleep_time: u32,}
-    //                                  second_run_time: u32, second_sleep_time: u32), Pmtk001Ack::Success);
     #[test]
-    #[ignore]
+    fn test_pmtk_225_cmd_periodic_mode() {
+        let mut gps = port_setup();
+        assert_eq!(gps.pmtk_225_cmd_periodic_mode(2, 3000, 12000, 18000, 72000), Pmtk001Ack::Success);
+        assert_eq!(gps.port.commands.last().unwrap(), "PMTK225,2,3000,12000,18000,72000");
+    }
+    #[test]
     fn test_pmtk_286_cmd_aic_mode() {
         assert_eq!(
             port_setup().pmtk_286_cmd_aic_mode(true),
@@ -1011,7 +1208,6 @@ mod pmtktests {
     }
 
     #[test]
-    #[ignore]
     fn test_pmtk_869_cmd_easy_enable() {
         assert_eq!(
             port_setup().pmtk_869_cmd_easy_enable(true),
@@ -1020,26 +1216,22 @@ mod pmtktests {
     }
 
     #[test]
-    #[ignore]
     fn test_pmtk_869_cmd_easy_query() {
         assert_eq!(port_setup().pmtk_869_cmd_easy_query(), true);
     }
 
     // fn test_ () {assert_eq!(port_setup().pmtk_187_locus_config(locus_interval: i8), Pmtk001Ack::Success);}
     #[test]
-    #[ignore]
     fn test_pmtk_330_api_set_datum() {
         assert_eq!(port_setup().pmtk_330_api_set_datum(0), Pmtk001Ack::Success);
     }
 
     #[test]
-    #[ignore]
     fn test_pmtk_430_api_q_datum() {
         assert_eq!(port_setup().pmtk_430_api_q_datum(), 0);
     }
 
     #[test]
-    #[ignore]
     fn test_pmtk_351_api_set_support_qzss_nmea() {
         assert_eq!(
             port_setup().pmtk_351_api_set_support_qzss_nmea(false),
@@ -1048,7 +1240,6 @@ mod pmtktests {
     }
 
     #[test]
-    #[ignore]
     fn test_pmtk_352_api_set_stop_qzss() {
         assert_eq!(
             port_setup().pmtk_352_api_set_stop_qzss(true),
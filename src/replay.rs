@@ -0,0 +1,250 @@
+//! # Replay
+//! Plays a raw NMEA capture (eg `cat /dev/serial0 > capture.nmea`) back as if it was the gps.
+//!
+//! ReplayPort is a GpsPort, so Gps::update() reads the capture the same as a live gps, garbage and
+//! all. Commands written to it are dropped.
+//!
+//! - paced false (default): the capture is given as fast as it is read.
+//! - paced true: each sentence with a UTC time (GGA, RMC, GLL, ZDA) is held back until that much
+//!   time has passed since the first one, so the capture plays in real time. The port gives nothing
+//!   while a sentence is held back, like a quiet gps, so a gap longer than the no_data_timeout of
+//!   the Gps gives NoConnection and the sentence comes with a later update.
+//! - looping true: the capture starts again from the top when it ends. Otherwise the port gives
+//!   nothing more, and update gives NoConnection.
+//! ```ignore
+//! let mut port = ReplayPort::open("flight.nmea").unwrap();
+//! port.paced = true;
+//! let mut gps = Gps::from_port(port);
+//! let sentence = gps.update();
+//! ```
+//!
+//! read_capture reads a whole capture into a Vec<GpsSentence>, the same as GpsSentence::read_from
+//! gives for saved sentences.
+
+use std::collections::VecDeque;
+use std::fs;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use crate::error::Error;
+use crate::nmea::datetime::NmeaTime;
+use crate::nmea::parse_nmea::parse_sentence;
+use crate::open_gps::gps::{Gps, GpsPort, GpsSentence, PortError};
+
+/// A recorded capture as a GpsPort.
+pub struct ReplayPort {
+    pub paced: bool,
+    pub looping: bool,
+    lines: Vec<Vec<u8>>,
+    next_line: usize,
+    output: VecDeque<u8>,
+    /// UTC (seconds of the day) of the first paced sentence and when it was given.
+    start: Option<(f64, Instant)>,
+    /// When the line in output may be given, if it is held back.
+    due: Option<Instant>,
+}
+
+/// Longest a read sleeps for a held back line, so read_line can still time out in between.
+const PACE_STEP: Duration = Duration::from_millis(10);
+
+impl ReplayPort {
+    /// Reads a capture file.
+    pub fn open(path: &str) -> Result<ReplayPort, Error> {
+        Ok(ReplayPort::from_bytes(&fs::read(path)?))
+    }
+
+    /// A capture already in memory. Lines are split on \n, everything else is given as recorded.
+    pub fn from_bytes(capture: &[u8]) -> ReplayPort {
+        ReplayPort {
+            paced: false,
+            looping: false,
+            lines: capture.split_inclusive(|byte| *byte == b'\n').map(|line| line.to_vec()).collect(),
+            next_line: 0,
+            output: VecDeque::new(),
+            start: None,
+            due: None,
+        }
+    }
+
+    /// True when the whole capture has been read and it isn't looping.
+    pub fn is_finished(&self) -> bool {
+        !self.looping && self.next_line == self.lines.len() && self.output.is_empty()
+    }
+
+    /// Queues the next line of the capture, false at the end of it.
+    fn next_line(&mut self) -> bool {
+        if self.next_line == self.lines.len() {
+            if !self.looping || self.lines.is_empty() {
+                return false;
+            }
+            self.next_line = 0;
+            self.start = None; // The times start again too.
+        }
+        let line = self.next_line;
+        self.next_line += 1;
+        if self.paced {
+            self.due = line_utc(&self.lines[line]).and_then(|utc| self.due_at(utc));
+        }
+        self.output.extend(&self.lines[line]);
+        true
+    }
+
+    /// When utc is as far from the first paced sentence as it was in the capture.
+    fn due_at(&mut self, utc: f64) -> Option<Instant> {
+        let (start_utc, start_time) = *self.start.get_or_insert((utc, Instant::now()));
+        let mut since_start = utc - start_utc;
+        if since_start < -43_200.0 {
+            since_start += 86_400.0; // Past midnight.
+        }
+        if !(0.0..=3_600.0).contains(&since_start) {
+            // Time jumped in the capture, carry on from here.
+            self.start = Some((utc, Instant::now()));
+            return None;
+        }
+        Some(start_time + Duration::from_secs_f64(since_start))
+    }
+}
+
+/// Reads a whole capture as fast as possible, eg to give to the geodesy tools.
+pub fn read_capture(path: &str) -> Result<Vec<GpsSentence>, Error> {
+    let mut gps = Gps::from_port(ReplayPort::open(path)?);
+    let mut sentences = Vec::new();
+    while !gps.port.is_finished() {
+        sentences.push(gps.update());
+    }
+    Ok(sentences)
+}
+
+/// The UTC time of a sentence, in seconds of the day. None if it has no time or isn't valid.
+fn line_utc(line: &[u8]) -> Option<f64> {
+    let line = core::str::from_utf8(line).ok()?;
+    let mut fields = parse_sentence(line)?;
+    let utc = match fields.next()?.get(3..6)? {
+        "GGA" | "RMC" | "ZDA" => fields.next()?,
+        "GLL" => fields.nth(4)?,
+        _ => return None,
+    };
+    utc.parse::<NmeaTime>().ok().map(|utc| utc.seconds_of_day())
+}
+
+impl GpsPort for ReplayPort {
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<usize, PortError> {
+        if self.output.is_empty() && !self.next_line() {
+            return Ok(0); // End of the capture.
+        }
+        if let Some(due) = self.due {
+            let now = Instant::now();
+            if due > now {
+                // Sleep a little at a time, the gap is the same as the gps saying nothing.
+                sleep((due - now).min(PACE_STEP));
+                return Ok(0);
+            }
+            self.due = None;
+        }
+        let mut size = 0;
+        while size < buf.len() {
+            match self.output.pop_front() {
+                Some(byte) => buf[size] = byte,
+                None => break,
+            }
+            size += 1;
+        }
+        Ok(size)
+    }
+
+    fn write_bytes(&mut self, _bytes: &[u8]) -> Result<(), PortError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod replay_test {
+    use std::time::{Duration, Instant};
+
+    use crate::{Gps, GpsOptions, GpsSentence};
+
+    use super::{line_utc, read_capture, ReplayPort};
+
+    const CAPTURE: &str = "\u{0}\u{1}garbage\r\n\
+        $GNGGA,131613.000,5132.7314,N,00005.9099,W,1,9,1.17,42.4,M,47.0,M,,*60\r\n\
+        $GPVTG,89.68,T,,M,0.00,N,0.0,K*5F\r\n\
+        $GNGGA,131613.200,5132.7314,N,00005.9099,W,1,9,1.17,42.4,M,47.0,M,,*62\r\n";
+
+    fn is_gga(sentence: GpsSentence) -> bool {
+        matches!(sentence, GpsSentence::GGA(_, _))
+    }
+
+    #[test]
+    fn replay_fast() {
+        let mut gps = Gps::from_port(ReplayPort::from_bytes(CAPTURE.as_bytes()));
+        assert_eq!(gps.update(), GpsSentence::InvalidSentence);
+        assert!(is_gga(gps.update()));
+        assert!(matches!(gps.update(), GpsSentence::VTG(_, _)));
+        assert!(is_gga(gps.update()));
+        assert_eq!(gps.update(), GpsSentence::NoConnection);
+    }
+
+    #[test]
+    fn replay_looping() {
+        let mut port = ReplayPort::from_bytes(CAPTURE.as_bytes());
+        port.looping = true;
+        let mut gps = Gps::from_port(port);
+        let ggas = (0..12).map(|_| gps.update()).filter(|s| matches!(s, GpsSentence::GGA(_, _))).count();
+        assert_eq!(ggas, 6);
+    }
+
+    #[test]
+    fn replay_paced() {
+        let mut port = ReplayPort::from_bytes(CAPTURE.as_bytes());
+        port.paced = true;
+        let mut gps = Gps::from_port(port);
+        gps.update();
+        assert!(is_gga(gps.update()));
+        let start = Instant::now();
+        gps.update();
+        assert!(is_gga(gps.update()));
+        // The second GGA is 200 ms after the first.
+        assert!(start.elapsed().as_millis() >= 150);
+    }
+
+    #[test]
+    fn replay_paced_gap_past_no_data_timeout() {
+        // 300 ms between the fixes, 100 ms no data time out.
+        let capture = CAPTURE.replace("131613.200", "131613.300").replace("*62", "*63");
+        let mut port = ReplayPort::from_bytes(capture.as_bytes());
+        port.paced = true;
+        let options = GpsOptions::default().no_data_timeout(Duration::from_millis(100));
+        let mut gps = Gps::from_port(port).with_options(options);
+        let start = Instant::now();
+        let mut sentences = Vec::new();
+        while !gps.port.is_finished() {
+            sentences.push(gps.update());
+        }
+        assert!(start.elapsed().as_millis() >= 250);
+        // The gap gives NoConnection, nothing is lost.
+        assert!(sentences.contains(&GpsSentence::NoConnection));
+        sentences.retain(|sentence| *sentence != GpsSentence::NoConnection);
+        assert_eq!(sentences.len(), 4);
+        assert_eq!(sentences[0], GpsSentence::InvalidSentence);
+        assert!(is_gga(sentences[1].clone()));
+        assert!(matches!(sentences[2], GpsSentence::VTG(_, _)));
+        assert!(is_gga(sentences[3].clone()));
+    }
+
+    #[test]
+    fn whole_capture() {
+        let path = std::env::temp_dir().join("adafruit_gps_replay_test.nmea");
+        std::fs::write(&path, CAPTURE).unwrap();
+        let sentences = read_capture(path.to_str().unwrap()).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(sentences.len(), 4);
+        assert!(is_gga(sentences[3].clone()));
+    }
+
+    #[test]
+    fn utc_of_lines() {
+        assert_eq!(line_utc(b"$GNGLL,5132.7314,N,00005.9099,W,131613.000,A,A*5A\r\n"), Some(47_773.0));
+        assert_eq!(line_utc(b"$GPVTG,89.68,T,,M,0.00,N,0.0,K*5F\r\n"), None);
+        assert_eq!(line_utc(b"$GNGGA,131613.000,5132.7314,N*00\r\n"), None);
+    }
+}
@@ -0,0 +1,222 @@
+//! # Uart
+//! Drivers for talking to the gps over a microcontroller uart instead of a linux serial port.
+//!
+//! Two kinds of uart are supported:
+//! - embedded-io: anything that implements embedded_io::Read + Write. Use Gps::from_io_uart.
+//! - embedded-hal-nb: anything that implements embedded_hal_nb::serial::Read + Write.
+//!   Use Gps::from_nb_uart.
+//!
+//! Both give a normal Gps, so update() and all the pmtk commands work the same as over a serial port.
+//! ```ignore
+//! let mut gps = Gps::from_io_uart(uart);
+//! gps.pmtk_220_set_nmea_updaterate("1000");
+//! let sentence = gps.update();
+//! ```
+
+use embedded_hal_nb::nb;
+use embedded_hal_nb::serial;
+use embedded_io::{Error, ErrorKind};
+
+use crate::open_gps::gps::{Gps, GpsPort, PortError};
+
+/// An embedded-io uart as a GpsPort.
+///
+/// embedded_io::Read blocks until there is a byte to read, so the uart has to give
+/// ErrorKind::TimedOut (or read 0 bytes) itself when nothing arrives from the gps.
+pub struct IoUart<U> {
+    pub uart: U,
+}
+
+impl<U: embedded_io::Read + embedded_io::Write> IoUart<U> {
+    pub fn new(uart: U) -> IoUart<U> {
+        IoUart { uart }
+    }
+}
+
+impl<U: embedded_io::Read + embedded_io::Write> GpsPort for IoUart<U> {
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<usize, PortError> {
+        match self.uart.read(buf) {
+            Ok(0) => Err(PortError::TimedOut),
+            Ok(size) => Ok(size),
+            Err(e) if e.kind() == ErrorKind::TimedOut => Err(PortError::TimedOut),
+            Err(_e) => Err(PortError::Other),
+        }
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), PortError> {
+        self.uart.write_all(bytes).map_err(|_e| PortError::Other)?;
+        self.uart.flush().map_err(|_e| PortError::Other)
+    }
+}
+
+/// An embedded-hal-nb uart as a GpsPort.
+///
+/// The uart is polled. After max_polls reads in a row with no byte (WouldBlock), reading gives up with
+/// PortError::TimedOut. Pick max_polls so that it is longer than the time between two sentences.
+pub struct NbUart<U> {
+    pub uart: U,
+    pub max_polls: u32,
+}
+
+impl<U: serial::Read<u8> + serial::Write<u8>> NbUart<U> {
+    pub fn new(uart: U, max_polls: u32) -> NbUart<U> {
+        NbUart { uart, max_polls }
+    }
+}
+
+impl<U: serial::Read<u8> + serial::Write<u8>> GpsPort for NbUart<U> {
+    fn read_bytes(&mut self, buf: &mut [u8]) -> Result<usize, PortError> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        for _poll in 0..self.max_polls {
+            match self.uart.read() {
+                Ok(byte) => {
+                    buf[0] = byte;
+                    return Ok(1);
+                }
+                Err(nb::Error::WouldBlock) => continue,
+                Err(nb::Error::Other(_e)) => return Err(PortError::Other),
+            }
+        }
+        Err(PortError::TimedOut)
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), PortError> {
+        for byte in bytes {
+            nb::block!(self.uart.write(*byte)).map_err(|_e| PortError::Other)?;
+        }
+        nb::block!(self.uart.flush()).map_err(|_e| PortError::Other)
+    }
+}
+
+impl<U: embedded_io::Read + embedded_io::Write> Gps<IoUart<U>> {
+    /// Gives a Gps reading from and writing to an embedded-io uart.
+    pub fn from_io_uart(uart: U) -> Gps<IoUart<U>> {
+        Gps::from_port(IoUart::new(uart))
+    }
+}
+
+impl<U: serial::Read<u8> + serial::Write<u8>> Gps<NbUart<U>> {
+    /// Gives a Gps reading from and writing to an embedded-hal-nb uart, see [NbUart](struct.NbUart.html)
+    /// for max_polls.
+    pub fn from_nb_uart(uart: U, max_polls: u32) -> Gps<NbUart<U>> {
+        Gps::from_port(NbUart::new(uart, max_polls))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod uart_test {
+    use core::convert::Infallible;
+    use std::collections::VecDeque;
+
+    use embedded_hal_nb::nb;
+    use embedded_hal_nb::serial;
+
+    use crate::{Gps, GpsSentence};
+    use crate::pmtk::send_pmtk::Pmtk001Ack;
+
+    /// Mock uart: gives the scripted bytes, then WouldBlock. Records everything written.
+    /// Every other nb read is a WouldBlock, like a uart that is faster than the gps.
+    struct MockUart {
+        input: VecDeque<u8>,
+        output: Vec<u8>,
+        poll: bool,
+    }
+
+    impl MockUart {
+        fn new(input: &str) -> MockUart {
+            MockUart { input: input.bytes().collect(), output: Vec::new(), poll: false }
+        }
+    }
+
+    impl embedded_io::ErrorType for MockUart {
+        type Error = Infallible;
+    }
+
+    impl embedded_io::Read for MockUart {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, Infallible> {
+            let mut size = 0;
+            while size < buf.len() {
+                match self.input.pop_front() {
+                    Some(byte) => buf[size] = byte,
+                    None => break,
+                }
+                size += 1;
+            }
+            Ok(size)
+        }
+    }
+
+    impl embedded_io::Write for MockUart {
+        fn write(&mut self, buf: &[u8]) -> Result<usize, Infallible> {
+            self.output.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<(), Infallible> {
+            Ok(())
+        }
+    }
+
+    impl serial::ErrorType for MockUart {
+        type Error = serial::ErrorKind;
+    }
+
+    impl serial::Read<u8> for MockUart {
+        fn read(&mut self) -> nb::Result<u8, serial::ErrorKind> {
+            self.poll = !self.poll;
+            if self.poll {
+                return Err(nb::Error::WouldBlock);
+            }
+            self.input.pop_front().ok_or(nb::Error::WouldBlock)
+        }
+    }
+
+    impl serial::Write<u8> for MockUart {
+        fn write(&mut self, word: u8) -> nb::Result<(), serial::ErrorKind> {
+            self.output.push(word);
+            Ok(())
+        }
+
+        fn flush(&mut self) -> nb::Result<(), serial::ErrorKind> {
+            Ok(())
+        }
+    }
+
+    const GGA: &str = "$GNGGA,131613.000,5132.7314,N,00005.9099,W,1,9,1.17,42.4,M,47.0,M,,*60\r\n";
+
+    #[test]
+    fn io_uart_update() {
+        let mut gps = Gps::from_io_uart(MockUart::new(GGA));
+        match gps.update() {
+            GpsSentence::GGA(_, gga) => assert_eq!(gga.satellites_used, 9),
+            other => panic!("Expected GGA, got {:?}", other),
+        }
+        assert_eq!(gps.update(), GpsSentence::NoConnection);
+    }
+
+    #[test]
+    fn io_uart_command() {
+        let mut gps = Gps::from_io_uart(MockUart::new("$PMTK001,220,3*30\r\n"));
+        assert_eq!(gps.pmtk_220_set_nmea_updaterate("1000"), Pmtk001Ack::Success);
+        assert_eq!(gps.port.uart.output, b"$PMTK220,1000*1F\r\n".to_vec());
+    }
+
+    #[test]
+    fn nb_uart_update() {
+        let mut gps = Gps::from_nb_uart(MockUart::new(GGA), 10);
+        match gps.update() {
+            GpsSentence::GGA(_, gga) => assert_eq!(gga.satellites_used, 9),
+            other => panic!("Expected GGA, got {:?}", other),
+        }
+        assert_eq!(gps.update(), GpsSentence::NoConnection);
+    }
+
+    #[test]
+    fn nb_uart_command() {
+        let mut gps = Gps::from_nb_uart(MockUart::new("$PMTK001,220,3*30\r\n"), 10);
+        assert_eq!(gps.pmtk_220_set_nmea_updaterate("1000"), Pmtk001Ack::Success);
+        assert_eq!(gps.port.uart.output, b"$PMTK220,1000*1F\r\n".to_vec());
+    }
+}